@@ -0,0 +1,26 @@
+use common_core::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_basepoint_mul(c: &mut Criterion) {
+    let scalar = Scalar::from(0x1234_5678_9abc_def0u64);
+
+    c.bench_function("scalar * RISTRETTO_BASEPOINT_POINT", |b| {
+        b.iter(|| black_box(&scalar) * RISTRETTO_BASEPOINT_POINT)
+    });
+    c.bench_function("mul_base", |b| b.iter(|| mul_base(black_box(&scalar))));
+}
+
+fn bench_compute_pubkeys(c: &mut Criterion) {
+    let scalars: Vec<Scalar> = (1..=1_000u64).map(Scalar::from).collect();
+
+    c.bench_function("compute_pubkeys x1000", |b| {
+        b.iter(|| compute_pubkeys(black_box(&scalars)))
+    });
+    #[cfg(feature = "rayon")]
+    c.bench_function("compute_pubkeys_par x1000", |b| {
+        b.iter(|| compute_pubkeys_par(black_box(&scalars)))
+    });
+}
+
+criterion_group!(benches, bench_basepoint_mul, bench_compute_pubkeys);
+criterion_main!(benches);
@@ -0,0 +1,178 @@
+use crate::prelude::*;
+use std::io::{self, BufRead, Read, Write};
+
+// Base58 isn't chunk-independent (the encoding of a buffer is not the
+// concatenation of encodings of its parts) and costs O(n^2) in the input
+// length, so large buffers are written as frames: each frame is the base58 of
+// at most `frame_len` input bytes, followed by `\n`. `\n` is outside the base58
+// alphabet, which makes decoding unambiguous: split on it, decode each frame
+// and concatenate. Frames may be shorter than `frame_len` (the last one, or
+// after an explicit `flush`); empty frames are never written.
+pub const DEFAULT_FRAME_LEN: usize = 1024;
+
+pub struct Base58Writer<W: Write> {
+    inner: W,
+    pending: Vec<u8>,
+    frame_len: usize,
+}
+
+impl<W: Write> Base58Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: Vec::with_capacity(DEFAULT_FRAME_LEN),
+            frame_len: DEFAULT_FRAME_LEN,
+        }
+    }
+
+    // A `frame_len` of 0 is treated as 1.
+    pub fn with_frame_len(inner: W, frame_len: usize) -> Self {
+        let frame_len = frame_len.max(1);
+        Self {
+            inner,
+            pending: Vec::with_capacity(frame_len),
+            frame_len,
+        }
+    }
+
+    // Writes the final partial frame and returns the inner writer. Dropping the
+    // writer without calling this (or `flush`) loses the buffered tail.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_frame(&mut self, end: usize) -> io::Result<()> {
+        let mut frame = bs58::encode(&self.pending[..end]).into_string();
+        frame.push('\n');
+        self.inner.write_all(frame.as_bytes())?;
+        self.pending.drain(..end);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Base58Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while self.pending.len() >= self.frame_len {
+            self.write_frame(self.frame_len)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            self.write_frame(self.pending.len())?;
+        }
+        self.inner.flush()
+    }
+}
+
+// Reads the framed format written by `Base58Writer`, one frame at a time.
+// A trailing `\r` and blank lines are tolerated; anything else that isn't
+// base58 is an `InvalidData` error.
+pub struct Base58Reader<R: BufRead> {
+    inner: R,
+    decoded: Vec<u8>,
+    position: usize,
+    line: String,
+}
+
+impl<R: BufRead> Base58Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            decoded: Vec::new(),
+            position: 0,
+            line: String::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Read for Base58Reader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            self.line.clear();
+            if self.inner.read_line(&mut self.line)? == 0 {
+                return Ok(0);
+            }
+            let frame = self.line.trim_end_matches(['\n', '\r']);
+            self.decoded = bs58::decode(frame)
+                .into_vec()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            self.position = 0;
+        }
+        let available = &self.decoded[self.position..];
+        let count = available.len().min(buf.len());
+        buf[..count].copy_from_slice(&available[..count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+// One-shot helpers over the framed format, for buffers already in memory.
+pub fn base58_encode_framed(data: &[u8], frame_len: usize) -> String {
+    let frame_len = frame_len.max(1);
+    data.chunks(frame_len)
+        .map(|frame| bs58::encode(frame).into_string() + "\n")
+        .collect()
+}
+
+pub fn base58_decode_framed(text: &str) -> AResult<Vec<u8>> {
+    let mut decoded = Vec::new();
+    for (index, frame) in text.lines().enumerate() {
+        let frame = bs58::decode(frame)
+            .into_vec()
+            .map_err(|err| aerr!("Invalid base58 in frame {index}: {err}"))?;
+        decoded.extend_from_slice(&frame);
+    }
+    Ok(decoded)
+}
+
+// The base58 alphabets in common use. Everything else in the crate encodes
+// with `Bitcoin`, which is also the default here.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Bs58Alphabet {
+    #[default]
+    Bitcoin,
+    Monero,
+    Ripple,
+    Flickr,
+    Custom(bs58::Alphabet),
+}
+
+impl Bs58Alphabet {
+    // Any 58 distinct ASCII characters.
+    pub fn custom(characters: &[u8; 58]) -> AResult<Self> {
+        bs58::Alphabet::new(characters)
+            .map(Self::Custom)
+            .map_err(|err| aerr!("Invalid base58 alphabet: {err}"))
+    }
+
+    fn alphabet(&self) -> &bs58::Alphabet {
+        match self {
+            Self::Bitcoin => bs58::Alphabet::BITCOIN,
+            Self::Monero => bs58::Alphabet::MONERO,
+            Self::Ripple => bs58::Alphabet::RIPPLE,
+            Self::Flickr => bs58::Alphabet::FLICKR,
+            Self::Custom(alphabet) => alphabet,
+        }
+    }
+}
+
+pub fn base58_encode_with(bytes: &[u8], alphabet: Bs58Alphabet) -> String {
+    bs58::encode(bytes)
+        .with_alphabet(alphabet.alphabet())
+        .into_string()
+}
+
+pub fn base58_decode_with(input: &str, alphabet: Bs58Alphabet) -> AResult<Vec<u8>> {
+    bs58::decode(input)
+        .with_alphabet(alphabet.alphabet())
+        .into_vec()
+        .map_err(|err| aerr!("Invalid base58 ({alphabet:?} alphabet): {err}"))
+}
@@ -0,0 +1,34 @@
+use crate::prelude::*;
+use std::str::FromStr;
+
+pub fn env_var(name: &str) -> AResult<String> {
+    std::env::var(name).map_err(|err| aerr!("Environment variable {name}: {err}"))
+}
+
+pub fn env_parse<T>(name: &str) -> AResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let raw = env_var(name)?;
+    raw.parse()
+        .map_err(|err| aerr!("Environment variable {name}={raw:?} is invalid: {err}"))
+}
+
+// Falls back to `default` when the variable is unset or does not parse; the
+// latter is logged since it usually means a misconfiguration.
+pub fn env_or<T>(name: &str, default: T) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match env_parse(name) {
+        Ok(value) => value,
+        Err(err) => {
+            if std::env::var_os(name).is_some() {
+                tracing::warn!("{err}; using the default");
+            }
+            default
+        }
+    }
+}
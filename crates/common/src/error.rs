@@ -0,0 +1,32 @@
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use thiserror::Error;
+
+// Error conditions callers are expected to special-case. They are carried
+// inside `anyhow::Error`, so recover them with `err.downcast_ref::<CommonError>()`.
+#[derive(Debug, Error)]
+pub enum CommonError {
+    #[error("request timed out")]
+    Timeout,
+    #[error("operation cancelled")]
+    Cancelled,
+    // The whole-operation budget of `take_data_deadline` ran out, as opposed
+    // to a single attempt hitting the client's per-request `Timeout`.
+    #[error("deadline of {0:?} exceeded")]
+    DeadlineExceeded(std::time::Duration),
+    // A 4xx/5xx response. The headers are kept so callers can act on
+    // `WWW-Authenticate`, `Retry-After` or rate-limit headers.
+    #[error("{body:#?}")]
+    Status {
+        status: StatusCode,
+        headers: HeaderMap,
+        body: String,
+    },
+    // The `error` member of a JSON-RPC 2.0 response; see `take_jsonrpc`.
+    #[error("JSON-RPC error {code}: {message}")]
+    JsonRpc {
+        code: i64,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+}
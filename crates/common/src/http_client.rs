@@ -1,13 +1,57 @@
 use crate::prelude::*;
-pub use reqwest::{self, header::HeaderMap, Client, RequestBuilder, Response};
+use ::reqwest::header::{HeaderName, HeaderValue};
+pub use ::reqwest::{self, header::HeaderMap, Client, Method, RequestBuilder, Response};
+pub use httpdate::HttpDate;
 pub use url::Url;
 
+#[cfg(all(feature = "jsonschema", not(target_arch = "wasm32")))]
+pub use ::jsonschema;
+
+mod lenient;
+mod req;
+mod retryable;
+mod single_flight;
+pub use lenient::*;
+pub use req::*;
+pub use retryable::*;
+pub use single_flight::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backoff;
+#[cfg(not(target_arch = "wasm32"))]
+mod base_url;
+#[cfg(not(target_arch = "wasm32"))]
+mod json_array;
+#[cfg(not(target_arch = "wasm32"))]
+mod middleware;
+#[cfg(not(target_arch = "wasm32"))]
+mod paginate;
+#[cfg(not(target_arch = "wasm32"))]
+mod sse;
+#[cfg(not(target_arch = "wasm32"))]
+pub use backoff::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use base_url::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use json_array::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use middleware::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use paginate::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sse::*;
+
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod blocking;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub use blocking::*;
+
 // Platform-specific imports and type aliases
 cfg_if! {
     if #[cfg(not(target_arch = "wasm32"))] {
         // Native-specific imports
         pub use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
-        pub use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+        pub use reqwest_retry::{policies::ExponentialBackoff, RetryError, RetryTransientMiddleware};
         pub use reqwest_tracing::TracingMiddleware;
         use tokio::time::Duration;
 
@@ -21,27 +65,712 @@ cfg_if! {
 
 // Common trait for both platforms
 #[async_trait::async_trait(?Send)]
-pub trait RequestBuilderExt {
+pub trait RequestBuilderExt: Sized {
+    // Sends the request, mapping transport timeouts to `CommonError::Timeout`.
+    async fn send_request(self) -> AResult<Response>;
+
+    fn with_header(self, name: HeaderName, value: HeaderValue) -> Self;
+
+    // Sets a pre-serialized body (protobuf, CBOR, ...) with its `Content-Type`.
+    fn body_bytes(self, bytes: Vec<u8>, content_type: &str) -> Self;
+
+    // Serializes `body` as JSON and gzips it, setting `Content-Encoding: gzip`.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn json_gzip<B: Serialize + ?Sized>(self, body: &B) -> AResult<Self>;
+
     async fn take_data<T>(self) -> AResult<T>
     where
-        T: serde::de::DeserializeOwned;
-}
+        T: serde::de::DeserializeOwned,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        let result: T = decode_json(response).await?;
+        Ok(result)
+    }
 
-// Implement the trait for the base reqwest::RequestBuilder, which is used in Wasm
-#[async_trait::async_trait(?Send)]
-impl RequestBuilderExt for RequestBuilder {
-    async fn take_data<T>(self) -> AResult<T>
+    // Like `take_data`, but a 2xx without a body (204, or an empty 200/202)
+    // yields `T::default()` instead of a decode error.
+    async fn take_data_or_default<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned + Default,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        if response.status() == reqwest::StatusCode::NO_CONTENT
+            || response.content_length() == Some(0)
+        {
+            return Ok(T::default());
+        }
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(from_reqwest)?;
+        if body.is_empty() {
+            return Ok(T::default());
+        }
+        decode_json_bytes(&headers, &body)
+    }
+
+    // Like `take_data`, but also rejects 3xx responses. Use it with clients that
+    // don't follow redirects, where a redirect is an application-level signal.
+    async fn take_data_strict<T>(self) -> AResult<T>
     where
         T: serde::de::DeserializeOwned,
     {
-        let response: Response = self.send().await?;
+        let response = self.send_request().await?;
         let status = response.status();
-        if status.is_client_error() || status.is_server_error() {
-            bail!("{:#?}", response.text().await?);
+        if status.is_redirection() {
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or("<none>");
+            bail!("Unexpected redirect {status} to Location: {location}");
+        }
+        let response = error_for_status(response).await?;
+        let result: T = decode_json(response).await?;
+        Ok(result)
+    }
+
+    // Like `take_data`, but also returns the response headers (pagination
+    // links, rate limits, ...).
+    async fn take_data_with_headers<T>(self) -> AResult<(HeaderMap, T)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        let headers = response.headers().clone();
+        let result: T = decode_json(response).await?;
+        Ok((headers, result))
+    }
+
+    // Decodes `{ "<field>": <T>, ... }` envelopes without a wrapper struct;
+    // sibling fields such as `meta` are ignored.
+    async fn take_enveloped<T>(self, field: &str) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut envelope: serde_json::Value = self.take_data().await?;
+        let payload = envelope
+            .get_mut(field)
+            .map(serde_json::Value::take)
+            .ok_or_else(|| aerr!("Response envelope has no {field:?} field"))?;
+        serde_json::from_value(payload)
+            .map_err(|err| aerr!("Failed to decode envelope field {field:?}: {err}"))
+    }
+
+    // Unwraps a JSON-RPC 2.0 response: `result` on success, or the `error`
+    // object as `CommonError::JsonRpc` so callers can match on its `code`.
+    async fn take_jsonrpc<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut envelope: serde_json::Value = self.take_data().await?;
+        if let Some(error) = envelope.get_mut("error").filter(|error| !error.is_null()) {
+            let code = error.get("code").and_then(serde_json::Value::as_i64);
+            let message = error.get("message").and_then(serde_json::Value::as_str);
+            let (Some(code), Some(message)) = (code, message) else {
+                bail!("Malformed JSON-RPC error object: {error}");
+            };
+            return Err(CommonError::JsonRpc {
+                code,
+                message: message.to_string(),
+                data: error.get_mut("data").map(serde_json::Value::take),
+            }
+            .into());
+        }
+        let result = envelope
+            .get_mut("result")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| aerr!("JSON-RPC response has neither \"result\" nor \"error\""))?;
+        serde_json::from_value(result)
+            .map_err(|err| aerr!("Failed to decode JSON-RPC result: {err}"))
+    }
+
+    // Conditional GET on `Last-Modified`: sends `If-Modified-Since` when `since`
+    // is given and maps a 304 to `Conditional::NotModified`. A fresh response
+    // carries the server's `Last-Modified` (if parseable) for the next call.
+    async fn take_data_if_modified_since<T>(
+        self,
+        since: Option<HttpDate>,
+    ) -> AResult<Conditional<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let builder = match since {
+            Some(since) => self.with_header(
+                reqwest::header::IF_MODIFIED_SINCE,
+                HeaderValue::from_str(&since.to_string())?,
+            ),
+            None => self,
+        };
+
+        let response = builder.send_request().await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+        let response = error_for_status(response).await?;
+        let last_modified = response
+            .headers()
+            .get(reqwest::header::LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<HttpDate>().ok());
+        let result: T = decode_json(response).await?;
+        Ok(Conditional::Fresh(result, last_modified))
+    }
+
+    // `take_data` under one budget for the whole operation, retries and
+    // backoff included; running out yields `CommonError::DeadlineExceeded`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_data_deadline<T>(self, deadline: Duration) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        tokio::time::timeout(deadline, self.take_data())
+            .await
+            .map_err(|_| CommonError::DeadlineExceeded(deadline))?
+    }
+
+    // Checks the body against `schema` before decoding it as `T`, failing with
+    // every violation and where it occurred (`/items/0/id: ...`).
+    #[cfg(all(feature = "jsonschema", not(target_arch = "wasm32")))]
+    async fn take_data_validated<T>(self, schema: &jsonschema::Validator) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let value: serde_json::Value = self.take_data().await?;
+        let violations: Vec<String> = schema
+            .iter_errors(&value)
+            .map(|err| format!("{}: {err}", err.instance_path))
+            .collect();
+        if !violations.is_empty() {
+            bail!("Response violates JSON Schema: {}", violations.join("; "));
+        }
+        serde_json::from_value(value)
+            .map_err(|err| aerr!("Failed to decode validated response: {err}"))
+    }
+
+    // Like `take_data`, but also returns every URL the request was redirected
+    // to, final URL last (empty without redirects). The hops are recorded by
+    // the redirect policy `OnionClient` installs, so other clients report none.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_data_with_redirects<T>(self) -> AResult<(Vec<Url>, T)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        REDIRECT_CHAIN
+            .scope(std::cell::RefCell::new(Vec::new()), async move {
+                let data = self.take_data().await?;
+                let chain = REDIRECT_CHAIN.with(std::cell::RefCell::take);
+                Ok((chain, data))
+            })
+            .await
+    }
+
+    // Like `take_data`, but undoes `Content-Encoding` (gzip, deflate, br) on the
+    // body first, for responses the HTTP stack left compressed.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_data_decompress<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        let headers = response.headers().clone();
+        let encodings: Vec<String> = response
+            .headers()
+            .get_all(reqwest::header::CONTENT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|encoding| encoding.trim().to_ascii_lowercase())
+            .filter(|encoding| !encoding.is_empty() && encoding != "identity")
+            .collect();
+        let mut body = response.bytes().await.map_err(from_reqwest)?.to_vec();
+        // Codings are listed in the order they were applied.
+        for encoding in encodings.iter().rev() {
+            body = decompress(encoding, &body)?;
+        }
+        decode_json_bytes(&headers, &body)
+    }
+
+    // Downloads the body, hashing each chunk as it arrives, and returns it only
+    // if its SHA-256 matches `expected_sha256`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_verified(self, expected_sha256: [u8; 32]) -> AResult<Vec<u8>> {
+        use futures::StreamExt;
+        use sha2::{Digest, Sha256};
+
+        let response = error_for_status(self.send_request().await?).await?;
+        // `Content-Length` is only a hint; don't let it reserve unbounded memory.
+        let capacity = response.content_length().unwrap_or(0).min(8 << 20) as usize;
+        let mut body = Vec::with_capacity(capacity);
+        let mut hasher = Sha256::new();
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(from_reqwest)?;
+            hasher.update(&chunk);
+            body.extend_from_slice(&chunk);
+        }
+
+        let actual: [u8; 32] = hasher.finalize().into();
+        if actual != expected_sha256 {
+            bail!(
+                "SHA-256 mismatch: expected {}, got {}",
+                hex::encode(expected_sha256),
+                hex::encode(actual)
+            );
+        }
+        Ok(body)
+    }
+
+    // Downloads into `path`, resuming from the end of an existing partial file
+    // with `Range: bytes=<offset>-`. A 206 is appended after checking its
+    // `Content-Range`; a 200 means the server ignored the range, so the file is
+    // restarted. Returns the final file size, checked against `total_expected`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn download_resumable<P>(self, path: P, total_expected: Option<u64>) -> AResult<u64>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        let path = path.as_ref();
+        let offset = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err.into()),
+        };
+        if total_expected == Some(offset) {
+            return Ok(offset);
+        }
+
+        let builder = if offset > 0 {
+            self.with_header(
+                reqwest::header::RANGE,
+                HeaderValue::from_str(&format!("bytes={offset}-"))?,
+            )
+        } else {
+            self
+        };
+        let response = error_for_status(builder.send_request().await?).await?;
+
+        let mut file = tokio::fs::OpenOptions::new();
+        let mut written = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+            let start = response
+                .headers()
+                .get(reqwest::header::CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(content_range_start);
+            if start != Some(offset) {
+                bail!("Server resumed at {start:?}, but {path:?} has {offset} bytes");
+            }
+            file.append(true);
+            offset
         } else {
-            let result: T = response.json().await?;
-            Ok(result)
+            file.write(true).truncate(true);
+            0
+        };
+        let mut file = file.create(true).open(path).await?;
+
+        let mut chunks = response.bytes_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.map_err(from_reqwest)?;
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        file.flush().await?;
+
+        if let Some(total) = total_expected.filter(|&total| total != written) {
+            bail!("Downloaded {written} bytes to {path:?}, expected {total}");
+        }
+        Ok(written)
+    }
+
+    // Like `take_data`, but records the status, body size and latency into
+    // `obs` once the body has been received, whether or not decoding succeeds.
+    // The buffered body is then put back into a `Response` so errors come out
+    // exactly as `take_data`'s do (`CommonError::Status`, `Content-Type` check).
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_data_observed<T>(self, obs: &mut RequestObs) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let started = std::time::Instant::now();
+        let response = self.send_request().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await.map_err(from_reqwest)?;
+        *obs = RequestObs {
+            status: Some(status),
+            body_bytes: body.len(),
+            elapsed: started.elapsed(),
+        };
+
+        let mut buffered = http::Response::new(body);
+        *buffered.status_mut() = status;
+        *buffered.headers_mut() = headers;
+        let response = error_for_status(Response::from(buffered)).await?;
+        decode_json(response).await
+    }
+
+    // Opens a Server-Sent Events stream; see `SseEvent`. Fails up front on an
+    // error status or a `Content-Type` other than `text/event-stream`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_sse(self) -> AResult<SseStream> {
+        let response = error_for_status(self.send_request().await?).await?;
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+        if let Some(content_type) =
+            content_type.filter(|value| !value.trim_start().starts_with("text/event-stream"))
+        {
+            bail!("Expected text/event-stream, got {content_type}");
+        }
+        Ok(sse::sse_stream(response))
+    }
+
+    // Streams the elements of a large top-level JSON array instead of
+    // buffering the whole body; see `JsonArrayStream`.
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn take_json_array_stream<T: serde::de::DeserializeOwned + 'static>(
+        self,
+    ) -> AResult<JsonArrayStream<T>> {
+        let response = error_for_status(self.send_request().await?).await?;
+        Ok(json_array::json_array_stream(response))
+    }
+
+    // Like `take_data`, but hands the raw body text to `deserialize` instead of
+    // decoding it with the strict `serde_json` defaults.
+    async fn take_data_with<T, F>(self, deserialize: F) -> AResult<T>
+    where
+        F: FnOnce(&str) -> AResult<T>,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        let text = response.text().await.map_err(from_reqwest)?;
+        deserialize(&text)
+    }
+
+    // Decodes the body as the wire DTO `T`, then converts it with `map`, so a
+    // response can land in a domain type in one call.
+    async fn take_map<U, T, F>(self, map: F) -> AResult<U>
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnOnce(T) -> AResult<U>,
+    {
+        map(self.take_data().await?)
+    }
+
+    // Like `take_data`, but a body that fails to decode is quoted (truncated)
+    // in the error, so malformed success responses can be diagnosed.
+    async fn take_data_debug<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.take_data_with(from_json).await
+    }
+
+    // Accepts numbers sent as strings; see `from_json_lenient`.
+    async fn take_data_lenient<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.take_data_with(from_json_lenient).await
+    }
+
+    // Like `take_data`, but tolerates a leading BOM and decodes the body with
+    // the `charset` from `Content-Type` (e.g. `ISO-8859-1`) instead of assuming
+    // UTF-8. A BOM, when present, overrides the declared charset.
+    async fn take_data_lenient_text<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let response = error_for_status(self.send_request().await?).await?;
+        let charset = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(charset_param)
+            .map(str::to_string);
+        let bytes = response.bytes().await.map_err(from_reqwest)?;
+        from_json(&decode_text(&bytes, charset.as_deref())?)
+    }
+}
+
+// Outcome of a conditional request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Conditional<T> {
+    // The resource changed (or no validator was sent); includes `Last-Modified`.
+    Fresh(T, Option<HttpDate>),
+    // The server answered 304: the cached copy is still current.
+    NotModified,
+}
+
+// What `take_data_observed` saw of a single call.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Debug, Default)]
+pub struct RequestObs {
+    status: Option<reqwest::StatusCode>,
+    body_bytes: usize,
+    elapsed: std::time::Duration,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl RequestObs {
+    // `None` until a response has been received.
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        self.status
+    }
+
+    pub fn body_bytes(&self) -> usize {
+        self.body_bytes
+    }
+
+    pub fn elapsed(&self) -> std::time::Duration {
+        self.elapsed
+    }
+}
+
+// Builds a `HeaderMap` from owned pairs, naming the offending header when a
+// name or value doesn't parse. Repeated names are appended, not replaced.
+pub fn headers_from_pairs<I>(pairs: I) -> AResult<HeaderMap>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| aerr!("Invalid header name {name:?}: {e}"))?;
+        let header_value = HeaderValue::from_str(&value)
+            .map_err(|e| aerr!("Invalid value for header {name:?}: {e}"))?;
+        headers.append(header_name, header_value);
+    }
+    Ok(headers)
+}
+
+// How `merge_headers` resolves a header name present in both maps.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergePolicy {
+    // The overlay's values replace all of the base's for that name.
+    #[default]
+    OverlayWins,
+    // The base's values are kept and the overlay's dropped.
+    BaseWins,
+    // Both are kept, base values first.
+    Append,
+}
+
+// Combines e.g. defaults (`base`) with per-call or auth headers (`overlay`).
+// Names that appear in only one map are always kept with all their values.
+pub fn merge_headers(mut base: HeaderMap, overlay: HeaderMap, policy: MergePolicy) -> HeaderMap {
+    let conflicts: Vec<HeaderName> = overlay
+        .keys()
+        .filter(|name| base.contains_key(*name))
+        .cloned()
+        .collect();
+    match policy {
+        MergePolicy::OverlayWins => {
+            for name in &conflicts {
+                base.remove(name);
+            }
+        }
+        MergePolicy::BaseWins | MergePolicy::Append => {}
+    }
+    for (name, value) in overlay.iter() {
+        if policy == MergePolicy::BaseWins && conflicts.contains(name) {
+            continue;
+        }
+        base.append(name, value.clone());
+    }
+    base
+}
+
+// Readiness probe: a GET that succeeds on any 2xx. The body is ignored, and
+// any other status is reported with the URL so probe logs are self-explanatory.
+pub async fn health_check(client: &RequestClient, url: &Url) -> AResult<()> {
+    let response = client
+        .get(url.clone())
+        .send_request()
+        .await
+        .map_err(|err| err.context(format!("Health check of {url} failed")))?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("Health check of {url} failed with status {status}");
+    }
+    Ok(())
+}
+
+// Polls until `pending` says the decoded body is final, for endpoints that
+// answer `{"status":"pending"}` while work completes. `request` builds a fresh
+// request per attempt; transport and status errors are returned immediately,
+// since the client middleware already retries those. Waits `delay` after each
+// pending answer so an eventually-consistent backend has time to catch up; a
+// zero delay suits long-poll endpoints that hold the request open themselves.
+pub async fn take_data_retry_if<T, B, F, P>(
+    mut request: F,
+    attempts: u32,
+    delay: std::time::Duration,
+    pending: P,
+) -> AResult<T>
+where
+    T: serde::de::DeserializeOwned,
+    B: RequestBuilderExt,
+    F: FnMut() -> B,
+    P: Fn(&T) -> bool,
+{
+    for attempt in 1..=attempts {
+        let data: T = request().take_data().await?;
+        if !pending(&data) {
+            return Ok(data);
         }
+        if attempt < attempts {
+            retryable::sleep(delay).await;
+        }
+    }
+    bail!("Response was still pending after {attempts} attempts")
+}
+
+async fn error_for_status(response: Response) -> AResult<Response> {
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        let headers = response.headers().clone();
+        let body = response.text().await.map_err(from_reqwest)?;
+        return Err(CommonError::Status {
+            status,
+            headers,
+            body,
+        }
+        .into());
+    }
+    Ok(response)
+}
+
+// Refuses to decode a body the server didn't label as JSON (an HTML error page
+// served with 200, say) with a message that says so, instead of a parse error.
+// A missing `Content-Type` is given the benefit of the doubt.
+async fn decode_json<T: serde::de::DeserializeOwned>(response: Response) -> AResult<T> {
+    let headers = response.headers().clone();
+    let body = response.bytes().await.map_err(from_reqwest)?;
+    decode_json_bytes(&headers, &body)
+}
+
+// `decode_json` for a body that has already been read (or decompressed).
+fn decode_json_bytes<T: serde::de::DeserializeOwned>(
+    headers: &HeaderMap,
+    body: &[u8],
+) -> AResult<T> {
+    let content_type = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned());
+    if let Some(content_type) = content_type.filter(|value| !is_json_content_type(value)) {
+        bail!(
+            "Expected JSON, got {content_type}: {:?}",
+            crate::json::snippet(&String::from_utf8_lossy(body))
+        );
+    }
+    Ok(serde_json::from_slice(body)?)
+}
+
+// The `charset` parameter of a `Content-Type` value, unquoted.
+fn charset_param(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+fn decode_text(bytes: &[u8], charset: Option<&str>) -> AResult<String> {
+    let encoding = match charset {
+        Some(label) => encoding_rs::Encoding::for_label(label.as_bytes())
+            .ok_or_else(|| aerr!("Unsupported response charset {label:?}"))?,
+        None => encoding_rs::UTF_8,
+    };
+    let (text, used, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        bail!("Response body is not valid {}", used.name());
+    }
+    Ok(text.into_owned())
+}
+
+// `application/json`, `text/json` and structured `+json` types such as
+// `application/problem+json`, ignoring parameters like `charset`.
+fn is_json_content_type(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    matches!(essence.as_str(), "application/json" | "text/json") || essence.ends_with("+json")
+}
+
+// First byte position of a `Content-Range: bytes <start>-<end>/<total>` value.
+#[cfg(not(target_arch = "wasm32"))]
+fn content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+// HTTP "deflate" is meant to be zlib-wrapped, but some servers send raw
+// deflate streams, so that is tried as a fallback.
+#[cfg(not(target_arch = "wasm32"))]
+fn decompress(encoding: &str, body: &[u8]) -> AResult<Vec<u8>> {
+    use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    let result = match encoding {
+        "gzip" | "x-gzip" => GzDecoder::new(body).read_to_end(&mut out),
+        "deflate" => ZlibDecoder::new(body).read_to_end(&mut out).or_else(|_| {
+            out.clear();
+            DeflateDecoder::new(body).read_to_end(&mut out)
+        }),
+        "br" => brotli_decompressor::Decompressor::new(body, 4096).read_to_end(&mut out),
+        other => bail!("Unsupported Content-Encoding {other:?}"),
+    };
+    result.map_err(|err| aerr!("Failed to decode {encoding} body: {err}"))?;
+    Ok(out)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn gzip_json<B: Serialize + ?Sized>(body: &B) -> AResult<Vec<u8>> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    serde_json::to_writer(&mut encoder, body)?;
+    Ok(encoder.finish()?)
+}
+
+fn from_reqwest(err: reqwest::Error) -> anyhow::Error {
+    if err.is_timeout() {
+        CommonError::Timeout.into()
+    } else {
+        err.into()
+    }
+}
+
+// Implement the trait for the base reqwest::RequestBuilder, which is used in Wasm
+#[async_trait::async_trait(?Send)]
+impl RequestBuilderExt for RequestBuilder {
+    async fn send_request(self) -> AResult<Response> {
+        self.send().await.map_err(from_reqwest)
+    }
+
+    fn with_header(self, name: HeaderName, value: HeaderValue) -> Self {
+        self.header(name, value)
+    }
+
+    fn body_bytes(self, bytes: Vec<u8>, content_type: &str) -> Self {
+        self.header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(bytes)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn json_gzip<B: Serialize + ?Sized>(self, body: &B) -> AResult<Self> {
+        let compressed = gzip_json(body)?;
+        Ok(self
+            .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            .body_bytes(compressed, "application/json"))
     }
 }
 
@@ -49,21 +778,149 @@ impl RequestBuilderExt for RequestBuilder {
 #[cfg(not(target_arch = "wasm32"))]
 #[async_trait::async_trait(?Send)]
 impl RequestBuilderExt for reqwest_middleware::RequestBuilder {
-    async fn take_data<T>(self) -> AResult<T>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let response: Response = self.send().await?;
-        let status = response.status();
-        if status.is_client_error() || status.is_server_error() {
-            bail!("{:#?}", response.text().await?);
+    async fn send_request(self) -> AResult<Response> {
+        send_middleware(self).await
+    }
+
+    fn with_header(self, name: HeaderName, value: HeaderValue) -> Self {
+        self.header(name, value)
+    }
+
+    fn body_bytes(self, bytes: Vec<u8>, content_type: &str) -> Self {
+        self.header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(bytes)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn json_gzip<B: Serialize + ?Sized>(self, body: &B) -> AResult<Self> {
+        let compressed = gzip_json(body)?;
+        Ok(self
+            .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            .body_bytes(compressed, "application/json"))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    // Set by `take_data_with_redirects` for the duration of its request.
+    static REDIRECT_CHAIN: std::cell::RefCell<Vec<Url>>;
+}
+
+// Follows up to `max` redirects, recording the hops for
+// `take_data_with_redirects` when it is waiting on this request. The chain is
+// rebuilt from `previous()` at each hop, so a retried request reports only its
+// own redirects.
+#[cfg(not(target_arch = "wasm32"))]
+fn redirect_policy(max: usize) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() > max {
+            return attempt.error(format!("Stopped after {max} redirects"));
+        }
+        let _ = REDIRECT_CHAIN.try_with(|chain| {
+            let mut hops: Vec<Url> = attempt.previous().iter().skip(1).cloned().collect();
+            hops.push(attempt.url().clone());
+            *chain.borrow_mut() = hops;
+        });
+        attempt.follow()
+    })
+}
+
+// `send_request` and `take_data` for the middleware builder as plain `Send`
+// futures, for code that must hand them to other threads (`SingleFlight`); the
+// `?Send` trait methods can't be.
+#[cfg(not(target_arch = "wasm32"))]
+async fn send_middleware(request: reqwest_middleware::RequestBuilder) -> AResult<Response> {
+    request.send().await.map_err(|err| {
+        if is_middleware_timeout(&err) {
+            CommonError::Timeout.into()
         } else {
-            let result: T = response.json().await?;
-            Ok(result)
+            err.into()
+        }
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn take_data_send<T>(request: reqwest_middleware::RequestBuilder) -> AResult<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let response = error_for_status(send_middleware(request).await?).await?;
+    decode_json(response).await
+}
+
+// The retry middleware wraps the transport error it gave up on, so unwrap it
+// before asking whether the failure was a timeout.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_middleware_timeout(err: &reqwest_middleware::Error) -> bool {
+    match err {
+        reqwest_middleware::Error::Reqwest(err) => err.is_timeout(),
+        reqwest_middleware::Error::Middleware(err) => match err.downcast_ref::<RetryError>() {
+            Some(RetryError::WithRetries { err, .. } | RetryError::Error(err)) => {
+                is_middleware_timeout(err)
+            }
+            None => err
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_timeout),
+        },
+    }
+}
+
+// The fetch `mode` applied to a request on WASM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CorsMode {
+    // The browser default, `mode: "cors"`.
+    #[default]
+    Cors,
+    // `mode: "no-cors"`, yielding an opaque response for cross-origin requests.
+    NoCors,
+}
+
+// Fetch API options. These only take effect on WASM; on native they are no-ops
+// so that shared code can call them unconditionally.
+pub trait FetchOptionsExt: Sized {
+    // Sets `credentials: "include"` so cookies are sent on cross-origin requests.
+    fn with_credentials_include(self) -> Self;
+    // Sets the fetch `mode` used for CORS.
+    fn with_cors_mode(self, mode: CorsMode) -> Self;
+}
+
+impl FetchOptionsExt for RequestBuilder {
+    fn with_credentials_include(self) -> Self {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                self.fetch_credentials_include()
+            } else {
+                self
+            }
+        }
+    }
+
+    fn with_cors_mode(self, mode: CorsMode) -> Self {
+        cfg_if! {
+            if #[cfg(target_arch = "wasm32")] {
+                match mode {
+                    CorsMode::Cors => self,
+                    CorsMode::NoCors => self.fetch_mode_no_cors(),
+                }
+            } else {
+                let _ = mode;
+                self
+            }
         }
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl FetchOptionsExt for reqwest_middleware::RequestBuilder {
+    fn with_credentials_include(self) -> Self {
+        self
+    }
+
+    fn with_cors_mode(self, _mode: CorsMode) -> Self {
+        self
+    }
+}
+
 // Native-only builder and implementation for the middleware-equipped client
 #[cfg(not(target_arch = "wasm32"))]
 #[derive(Builder)]
@@ -77,12 +934,118 @@ pub struct OnionClient {
     max_retry_interval: u64,
     #[builder(default = "None")]
     api_key: Option<String>,
+    #[builder(default = "false")]
+    request_id: bool,
+    #[builder(default = "true")]
+    tracing: bool,
+    #[builder(default = "None")]
+    pool_idle_timeout: Option<u64>,
+    #[builder(default = "None")]
+    pool_max_idle_per_host: Option<usize>,
+    #[builder(default = "false")]
+    http2_prior_knowledge: bool,
+    #[builder(default = "false")]
+    http1_only: bool,
+    #[builder(default = "None")]
+    correlation_id: Option<String>,
+    #[builder(default)]
+    backoff: BackoffKind,
+    // Source IP for outbound connections on multi-homed hosts.
+    #[builder(default = "None")]
+    local_address: Option<std::net::IpAddr>,
+    // Static `host -> address` overrides that bypass DNS. The port still comes
+    // from the URL; the one in the address is ignored.
+    #[builder(default)]
+    resolve: Vec<(String, std::net::SocketAddr)>,
+    // HMAC-signs every request; see `SigningMiddleware`.
+    #[cfg(feature = "hash")]
+    #[builder(default = "None")]
+    request_signing: Option<SigningMiddleware>,
+    // Redirects followed before the request fails, as in reqwest's default policy.
+    #[builder(default = "10")]
+    max_redirects: usize,
+    // Query parameters added to every request unless the URL already has them.
+    #[builder(default)]
+    default_query: Vec<(String, String)>,
+    // SOCKS port of the local Tor daemon, used for `.onion` hosts only.
+    #[cfg(feature = "tor")]
+    #[builder(default = "Some(std::net::SocketAddr::from(([127, 0, 0, 1], 9050)))")]
+    tor_socks: Option<std::net::SocketAddr>,
+    // Adds W3C trace context headers from the current OpenTelemetry context.
+    #[cfg(feature = "otel")]
+    #[builder(default = "false")]
+    otel_propagation: bool,
+    // Only used when converting into a `BaseUrlClient`; converting a config
+    // that sets it into a plain `ClientWithMiddleware` is an error, since
+    // relative paths would silently stop resolving.
+    #[builder(default = "None")]
+    base_url: Option<Url>,
+}
+
+// Upper bound accepted by `OnionClientBuilder::build_validated`.
+#[cfg(not(target_arch = "wasm32"))]
+pub const MAX_RETRIES: u32 = 10;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OnionClientBuilder {
+    // `build` plus sanity checks on the values, so a nonsensical client is
+    // rejected here instead of misbehaving at request time. `build` itself is
+    // left unchecked for compatibility.
+    pub fn build_validated(&self) -> AResult<OnionClient> {
+        let config = self.build()?;
+        if config.timeout == 0 {
+            bail!("timeout must be greater than 0 ms");
+        }
+        if config.max_retry_interval < config.timeout {
+            bail!(
+                "max_retry_interval ({} ms) must be at least timeout ({} ms), \
+                 which is also the minimum retry interval",
+                config.max_retry_interval,
+                config.timeout
+            );
+        }
+        if config.retry > MAX_RETRIES {
+            bail!("retry must be at most {MAX_RETRIES}, got {}", config.retry);
+        }
+        config.check_protocol_flags()?;
+        Ok(config)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 impl OnionClient {
+    // Shared by `build_validated` and the client conversion, which can't build
+    // a client from contradictory protocol settings either way.
+    fn check_protocol_flags(&self) -> AResult<()> {
+        if self.http2_prior_knowledge && self.http1_only {
+            bail!("http2_prior_knowledge and http1_only are mutually exclusive");
+        }
+        Ok(())
+    }
+
     pub fn from_env() -> AResult<ClientWithMiddleware> {
-        let api_key = std::env::var("API_KEY").ok();
+        let api_key = api_key_from_env();
+        OnionClientBuilder::default()
+            .retry(0u32)
+            .api_key(api_key)
+            .build()?
+            .into()
+    }
+
+    // Like `from_env`, but falls back to reading the key from the file named
+    // by `API_KEY_FILE` (e.g. a mounted secret) when `API_KEY` is unset or blank.
+    pub fn from_env_or_file() -> AResult<ClientWithMiddleware> {
+        let api_key = match api_key_from_env() {
+            Some(api_key) => Some(api_key),
+            None => match std::env::var_os("API_KEY_FILE") {
+                Some(path) => {
+                    let contents = std::fs::read_to_string(&path)
+                        .map_err(|err| aerr!("Failed to read API_KEY_FILE {path:?}: {err}"))?;
+                    normalize_api_key(&contents)
+                }
+                None => None,
+            },
+        };
         OnionClientBuilder::default()
             .retry(0u32)
             .api_key(api_key)
@@ -99,6 +1062,55 @@ impl OnionClient {
     }
 }
 
+// A process-wide client built by `OnionClient::from_env` on first use, so the
+// connection pool is shared instead of rebuilt per call. A failed build is
+// returned to the caller and retried on the next call rather than cached.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shared_client() -> AResult<&'static RequestClient> {
+    static SHARED: std::sync::OnceLock<RequestClient> = std::sync::OnceLock::new();
+
+    if let Some(client) = SHARED.get() {
+        return Ok(client);
+    }
+    // Racing first callers may each build one; only the first is kept.
+    let client = OnionClient::from_env()?;
+    Ok(SHARED.get_or_init(|| client))
+}
+
+#[cfg(all(feature = "tor", not(target_arch = "wasm32")))]
+impl OnionClient {
+    pub fn tor_socks(&self) -> Option<std::net::SocketAddr> {
+        self.tor_socks
+    }
+}
+
+// The proxy for `url` when Tor routing is on: `.onion` hosts go through
+// `socks5h://`, which makes Tor resolve the name so it never hits local DNS.
+// Everything else connects directly.
+#[cfg(all(feature = "tor", not(target_arch = "wasm32")))]
+pub fn onion_proxy_url(tor_socks: std::net::SocketAddr, url: &Url) -> Option<Url> {
+    let is_onion = url
+        .host_str()
+        .is_some_and(|host| host.trim_end_matches('.').ends_with(".onion"));
+    is_onion
+        .then(|| Url::parse(&format!("socks5h://{tor_socks}")).ok())
+        .flatten()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn api_key_from_env() -> Option<String> {
+    std::env::var("API_KEY")
+        .ok()
+        .and_then(|api_key| normalize_api_key(&api_key))
+}
+
+// A blank key would otherwise produce a broken `Authorization: Bearer ` header.
+#[cfg(not(target_arch = "wasm32"))]
+fn normalize_api_key(api_key: &str) -> Option<String> {
+    let trimmed = api_key.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<OnionClient> for AResult<ClientWithMiddleware> {
     fn from(config: OnionClient) -> Self {
@@ -109,21 +1121,92 @@ impl From<OnionClient> for AResult<ClientWithMiddleware> {
             )
             .build_with_max_retries(config.retry);
 
+        config.check_protocol_flags()?;
+        if let Some(base_url) = &config.base_url {
+            bail!("base_url ({base_url}) is only used by BaseUrlClient; convert into that instead");
+        }
+
         let mut builder = Client::builder();
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if config.http1_only {
+            builder = builder.http1_only();
+        }
+        if let Some(idle_timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(Duration::from_millis(idle_timeout));
+        }
+        if let Some(max_idle) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if let Some(local_address) = config.local_address {
+            builder = builder.local_address(local_address);
+        }
+        builder = builder.redirect(redirect_policy(config.max_redirects));
+        for (host, addr) in &config.resolve {
+            builder = builder.resolve(host, *addr);
+        }
+        #[cfg(feature = "tor")]
+        if let Some(tor_socks) = config.tor_socks {
+            let proxy = reqwest::Proxy::custom(move |url| onion_proxy_url(tor_socks, url));
+            builder = builder.proxy(proxy);
+        }
 
-        if let Some(api_key) = config.api_key {
+        if let Some(api_key) = config.api_key.as_deref().and_then(normalize_api_key) {
             let mut headers = HeaderMap::new();
-            let value = format!("Bearer {api_key}").parse()?;
-            headers.insert("Authorization", value);
+            // The key itself is left out of the error so it never reaches logs.
+            let mut value = HeaderValue::from_str(&format!("Bearer {api_key}")).map_err(|_| {
+                aerr!(
+                    "API key contains invalid header characters \
+                     (only visible ASCII is allowed, no control characters such as newlines)"
+                )
+            })?;
+            value.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, value);
             builder = builder.default_headers(headers);
         }
 
         let client = builder.build().map_err(msg)?;
-        let client_with_middleware = ClientBuilder::new(client)
-            .with(TracingMiddleware::default())
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-            .build();
+        let mut client_builder = ClientBuilder::new(client);
+        if config.tracing {
+            client_builder = client_builder.with(TracingMiddleware::default());
+        }
+        if let Some(correlation_id) = config.correlation_id {
+            client_builder = client_builder.with(CorrelationIdMiddleware::new(correlation_id));
+        }
+        if config.request_id {
+            client_builder = client_builder.with(RequestIdMiddleware);
+        }
+        if !config.default_query.is_empty() {
+            client_builder = client_builder.with(DefaultQueryMiddleware::new(config.default_query));
+        }
+        #[cfg(feature = "otel")]
+        if config.otel_propagation {
+            client_builder = client_builder.with(TraceContextMiddleware);
+        }
+        #[cfg(feature = "metrics")]
+        {
+            client_builder = client_builder.with(MetricsMiddleware);
+        }
+        let client_builder = match config.backoff {
+            BackoffKind::Exponential => {
+                client_builder.with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            }
+            BackoffKind::FullJitter { base, cap } => client_builder.with(
+                RetryTransientMiddleware::new_with_policy(FullJitterBackoff {
+                    base,
+                    cap,
+                    max_retries: config.retry,
+                }),
+            ),
+        };
+        // Inside the retry middleware, so every attempt is signed afresh.
+        #[cfg(feature = "hash")]
+        let client_builder = match config.request_signing {
+            Some(signing) => client_builder.with(signing),
+            None => client_builder,
+        };
 
-        Ok(client_with_middleware)
+        Ok(client_builder.build())
     }
 }
@@ -0,0 +1,48 @@
+use reqwest_retry::{RetryDecision, RetryPolicy};
+use std::time::{Duration, SystemTime};
+
+// How `OnionClient` spaces out retries of transient failures.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackoffKind {
+    // Bounded exponential backoff between `timeout` and `max_retry_interval`.
+    #[default]
+    Exponential,
+    // "Full jitter": each wait is uniform in `0..=min(cap, base * 2^attempt)`
+    // milliseconds, which spreads out clients that failed at the same moment.
+    FullJitter {
+        base: u64,
+        cap: u64,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FullJitterBackoff {
+    pub(crate) base: u64,
+    pub(crate) cap: u64,
+    pub(crate) max_retries: u32,
+}
+
+impl RetryPolicy for FullJitterBackoff {
+    fn should_retry(&self, _request_start_time: SystemTime, n_past_retries: u32) -> RetryDecision {
+        if n_past_retries >= self.max_retries {
+            return RetryDecision::DoNotRetry;
+        }
+        RetryDecision::Retry {
+            execute_after: SystemTime::now()
+                + full_jitter_delay(self.base, self.cap, n_past_retries),
+        }
+    }
+}
+
+// One full-jitter sample for the given (zero-based) retry attempt. If the OS
+// RNG fails, the ceiling is used: still bounded, just not spread out.
+pub fn full_jitter_delay(base: u64, cap: u64, attempt: u32) -> Duration {
+    let ceiling = cap.min(base.saturating_mul(2u64.saturating_pow(attempt)));
+    let mut random = [0u8; 8];
+    let millis = match (getrandom::getrandom(&mut random), ceiling.checked_add(1)) {
+        (Ok(()), Some(span)) => u64::from_le_bytes(random) % span,
+        (Ok(()), None) => u64::from_le_bytes(random),
+        (Err(_), _) => ceiling,
+    };
+    Duration::from_millis(millis)
+}
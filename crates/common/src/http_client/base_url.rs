@@ -0,0 +1,77 @@
+use super::OnionClient;
+use crate::prelude::*;
+use reqwest_middleware::{ClientWithMiddleware, RequestBuilder};
+
+// A client that resolves relative paths against `OnionClient::base_url`.
+// Paths are always taken as relative to the base, so `"users"` and `"/users"`
+// both land under `https://host/api/` rather than at the host root. Absolute
+// URLs still go through the wrapped client, which this derefs to.
+#[derive(Clone, Debug)]
+pub struct BaseUrlClient {
+    client: ClientWithMiddleware,
+    base_url: Option<Url>,
+}
+
+impl BaseUrlClient {
+    pub fn base_url(&self) -> Option<&Url> {
+        self.base_url.as_ref()
+    }
+
+    pub fn url_for(&self, path: &str) -> AResult<Url> {
+        let base = self
+            .base_url
+            .as_ref()
+            .ok_or_else(|| aerr!("No base_url is set; cannot resolve relative path {path:?}"))?;
+        base.join(path.trim_start_matches('/'))
+            .map_err(|err| aerr!("Cannot join {path:?} onto {base}: {err}"))
+    }
+
+    pub fn request_rel(&self, method: Method, path: &str) -> AResult<RequestBuilder> {
+        Ok(self.client.request(method, self.url_for(path)?))
+    }
+
+    pub fn get_rel(&self, path: &str) -> AResult<RequestBuilder> {
+        self.request_rel(Method::GET, path)
+    }
+
+    pub fn post_rel(&self, path: &str) -> AResult<RequestBuilder> {
+        self.request_rel(Method::POST, path)
+    }
+
+    pub fn put_rel(&self, path: &str) -> AResult<RequestBuilder> {
+        self.request_rel(Method::PUT, path)
+    }
+
+    pub fn patch_rel(&self, path: &str) -> AResult<RequestBuilder> {
+        self.request_rel(Method::PATCH, path)
+    }
+
+    pub fn delete_rel(&self, path: &str) -> AResult<RequestBuilder> {
+        self.request_rel(Method::DELETE, path)
+    }
+}
+
+impl std::ops::Deref for BaseUrlClient {
+    type Target = ClientWithMiddleware;
+
+    fn deref(&self) -> &ClientWithMiddleware {
+        &self.client
+    }
+}
+
+impl From<OnionClient> for AResult<BaseUrlClient> {
+    fn from(mut config: OnionClient) -> Self {
+        // Without a trailing slash `Url::join` would replace the last segment.
+        let base_url = config.base_url.take().map(|mut url| {
+            if !url.path().ends_with('/') {
+                url.set_path(&format!("{}/", url.path()));
+            }
+            url
+        });
+        let client: AResult<ClientWithMiddleware> = config.into();
+        Ok(BaseUrlClient {
+            client: client?,
+            base_url,
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use crate::prelude::*;
+use std::future::Future;
+
+// Sync facade for callers without an async runtime (CLI tools, build scripts).
+// Each call spins up a throwaway current-thread runtime, so it must not be used
+// from inside an async context: Tokio panics on nested `block_on`.
+pub trait BlockingClientExt {
+    fn get_json_blocking<T>(&self, url: &str) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned;
+}
+
+impl BlockingClientExt for RequestClient {
+    fn get_json_blocking<T>(&self, url: &str) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.get(url).take_data_blocking()
+    }
+}
+
+pub trait BlockingRequestExt: RequestBuilderExt {
+    fn take_data_blocking<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        block_on(self.take_data())?
+    }
+}
+
+impl<B: RequestBuilderExt> BlockingRequestExt for B {}
+
+fn block_on<F: Future>(future: F) -> AResult<F::Output> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(future))
+}
@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use futures::stream::{self, LocalBoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+
+pub type JsonArrayStream<T> = LocalBoxStream<'static, AResult<T>>;
+
+#[derive(Default, PartialEq)]
+enum Position {
+    #[default]
+    BeforeArray,
+    InArray,
+    Done,
+}
+
+// Splits a top-level JSON array into the raw bytes of its elements as chunks
+// arrive, without parsing them: it only tracks nesting depth and string
+// boundaries, so memory is bounded by the largest element rather than the body.
+#[derive(Default)]
+struct JsonArraySplitter {
+    position: Position,
+    element: Vec<u8>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    elements: usize,
+}
+
+impl JsonArraySplitter {
+    fn feed(&mut self, chunk: &[u8], out: &mut VecDeque<AResult<Vec<u8>>>) {
+        for &byte in chunk {
+            match self.position {
+                Position::Done => return,
+                Position::BeforeArray => match byte {
+                    b'[' => self.position = Position::InArray,
+                    byte if byte.is_ascii_whitespace() => {}
+                    byte => {
+                        self.position = Position::Done;
+                        out.push_back(Err(aerr!(
+                            "Expected a JSON array, found {:?}",
+                            char::from(byte)
+                        )));
+                    }
+                },
+                Position::InArray => self.in_array(byte, out),
+            }
+        }
+    }
+
+    fn in_array(&mut self, byte: u8, out: &mut VecDeque<AResult<Vec<u8>>>) {
+        if self.in_string {
+            self.element.push(byte);
+            match byte {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.in_string = false,
+                _ => {}
+            }
+            return;
+        }
+        match byte {
+            b',' if self.depth == 0 => self.flush(out, false),
+            b']' if self.depth == 0 => {
+                self.flush(out, true);
+                self.position = Position::Done;
+            }
+            _ => {
+                match byte {
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                }
+                self.element.push(byte);
+            }
+        }
+    }
+
+    // `[]` has no elements, but `[1,]` and `[1,,2]` contain an empty one.
+    fn flush(&mut self, out: &mut VecDeque<AResult<Vec<u8>>>, closing: bool) {
+        let element = std::mem::take(&mut self.element);
+        if element.iter().all(u8::is_ascii_whitespace) {
+            if !closing || self.elements > 0 {
+                out.push_back(Err(aerr!("Empty element at index {}", self.elements)));
+            }
+        } else {
+            out.push_back(Ok(element));
+        }
+        self.elements += 1;
+    }
+
+    fn finish(&mut self) -> Option<AResult<Vec<u8>>> {
+        match self.position {
+            Position::Done => None,
+            _ => {
+                self.position = Position::Done;
+                Some(Err(aerr!("JSON array ended before its closing ']'")))
+            }
+        }
+    }
+}
+
+// Streams the elements of a top-level JSON array body, decoding each as it
+// completes. An element that fails to decode is reported as an error item and
+// the stream carries on; a body that isn't an array, or is cut short, ends it.
+pub(crate) fn json_array_stream<T: DeserializeOwned + 'static>(
+    response: Response,
+) -> JsonArrayStream<T> {
+    let state = (
+        response.bytes_stream().boxed(),
+        JsonArraySplitter::default(),
+        VecDeque::new(),
+    );
+    stream::unfold(state, |(mut body, mut splitter, mut ready)| async move {
+        loop {
+            if let Some(element) = ready.pop_front() {
+                return Some((decode(element), (body, splitter, ready)));
+            }
+            if splitter.position == Position::Done {
+                return None;
+            }
+            match body.next().await {
+                Some(Ok(chunk)) => splitter.feed(&chunk, &mut ready),
+                Some(Err(err)) => return Some((Err(err.into()), (body, splitter, ready))),
+                None => {
+                    let element = splitter.finish()?;
+                    return Some((decode(element), (body, splitter, ready)));
+                }
+            }
+        }
+    })
+    .boxed_local()
+}
+
+fn decode<T: DeserializeOwned>(element: AResult<Vec<u8>>) -> AResult<T> {
+    let element = element?;
+    serde_json::from_slice(&element).map_err(|err| {
+        aerr!(
+            "Invalid array element {:?}: {err}",
+            crate::json::snippet(&String::from_utf8_lossy(&element))
+        )
+    })
+}
@@ -0,0 +1,264 @@
+use crate::prelude::*;
+use http::Extensions;
+use reqwest::{header::HeaderValue, Request};
+use reqwest_middleware::{Middleware, Next};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Tags every outbound request with a fresh UUID in `X-Request-Id`, unless the
+// caller already set one, and runs the request inside a span carrying that ID.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RequestIdMiddleware;
+
+#[async_trait::async_trait]
+impl Middleware for RequestIdMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let request_id = match req.headers().get(REQUEST_ID_HEADER) {
+            Some(existing) => existing.to_str().unwrap_or_default().to_string(),
+            None => {
+                let generated = Uuid::new_v4().to_string();
+                let value = HeaderValue::from_str(&generated)
+                    .map_err(reqwest_middleware::Error::middleware)?;
+                req.headers_mut().insert(REQUEST_ID_HEADER, value);
+                generated
+            }
+        };
+
+        let span = tracing::info_span!("http_request", request_id = %request_id);
+        next.run(req, extensions).instrument(span).await
+    }
+}
+
+// Runs every request inside a span carrying a caller-supplied
+// `correlation_id`, so logs can be stitched together across services. When
+// installed after `TracingMiddleware` the span nests under its HTTP span.
+#[derive(Clone, Debug)]
+pub struct CorrelationIdMiddleware {
+    correlation_id: String,
+}
+
+impl CorrelationIdMiddleware {
+    pub fn new(correlation_id: impl Into<String>) -> Self {
+        Self {
+            correlation_id: correlation_id.into(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for CorrelationIdMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let span = tracing::info_span!(
+            "http_correlation",
+            correlation_id = %self.correlation_id
+        );
+        next.run(req, extensions).instrument(span).await
+    }
+}
+
+// Appends fixed query parameters (e.g. `api_version=2`) to every request. A
+// key the caller already put in the URL is left alone, so per-call values win.
+#[derive(Clone, Debug)]
+pub struct DefaultQueryMiddleware {
+    params: Vec<(String, String)>,
+}
+
+impl DefaultQueryMiddleware {
+    pub fn new(params: Vec<(String, String)>) -> Self {
+        Self { params }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for DefaultQueryMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let url = req.url_mut();
+        let present: Vec<String> = url.query_pairs().map(|(key, _)| key.into_owned()).collect();
+        let missing: Vec<&(String, String)> = self
+            .params
+            .iter()
+            .filter(|(key, _)| !present.contains(key))
+            .collect();
+        if !missing.is_empty() {
+            url.query_pairs_mut().extend_pairs(missing);
+        }
+        next.run(req, extensions).await
+    }
+}
+
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+// Signs each request with `sign_request_parts` over its method, path and query,
+// the Unix timestamp sent in `X-Signature-Timestamp`, and the body, putting the
+// hex signature in the configured header. Streaming bodies can't be hashed
+// up front and are refused.
+#[cfg(feature = "hash")]
+#[derive(Clone)]
+pub struct SigningMiddleware {
+    secret: Vec<u8>,
+    signature_header: reqwest::header::HeaderName,
+}
+
+#[cfg(feature = "hash")]
+impl SigningMiddleware {
+    pub fn new(secret: impl Into<Vec<u8>>, signature_header: &str) -> AResult<Self> {
+        let signature_header = reqwest::header::HeaderName::from_bytes(signature_header.as_bytes())
+            .map_err(|err| aerr!("Invalid signature header name {signature_header:?}: {err}"))?;
+        Ok(Self {
+            secret: secret.into(),
+            signature_header,
+        })
+    }
+}
+
+// The secret is left out so configs can be logged.
+#[cfg(feature = "hash")]
+impl std::fmt::Debug for SigningMiddleware {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SigningMiddleware")
+            .field("signature_header", &self.signature_header)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "hash")]
+#[async_trait::async_trait]
+impl Middleware for SigningMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let body = match req.body() {
+            None => &[][..],
+            Some(body) => body.as_bytes().ok_or_else(|| {
+                reqwest_middleware::Error::Middleware(aerr!("Cannot sign a streaming request body"))
+            })?,
+        };
+        let path = match req.url().query() {
+            Some(query) => format!("{}?{query}", req.url().path()),
+            None => req.url().path().to_string(),
+        };
+        let timestamp = crate::token::unix_now();
+        let signature =
+            sign_request_parts(&self.secret, req.method().as_str(), &path, timestamp, body);
+
+        let headers = req.headers_mut();
+        headers.insert(SIGNATURE_TIMESTAMP_HEADER, HeaderValue::from(timestamp));
+        headers.insert(
+            self.signature_header.clone(),
+            HeaderValue::from_str(&signature).map_err(reqwest_middleware::Error::middleware)?,
+        );
+        next.run(req, extensions).await
+    }
+}
+
+pub const REQUESTS_TOTAL_METRIC: &str = "http_client_requests_total";
+pub const REQUEST_DURATION_METRIC: &str = "http_client_request_duration_seconds";
+
+// Records one `http_client_requests_total` count per request, labelled with
+// `outcome` ("success"/"error") and `status_class` ("2xx", ..., or "none" when
+// no response arrived), plus the latency in `http_client_request_duration_seconds`.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetricsMiddleware;
+
+#[cfg(feature = "metrics")]
+#[async_trait::async_trait]
+impl Middleware for MetricsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let started = std::time::Instant::now();
+        let result = next.run(req, extensions).await;
+        let elapsed = started.elapsed();
+
+        let (outcome, status_class) = match &result {
+            Ok(response) => {
+                let status = response.status();
+                let outcome = if status.is_client_error() || status.is_server_error() {
+                    "error"
+                } else {
+                    "success"
+                };
+                (outcome, format!("{}xx", status.as_u16() / 100))
+            }
+            Err(_) => ("error", "none".to_string()),
+        };
+
+        metrics::counter!(
+            REQUESTS_TOTAL_METRIC,
+            "outcome" => outcome,
+            "status_class" => status_class
+        )
+        .increment(1);
+        metrics::histogram!(REQUEST_DURATION_METRIC, "outcome" => outcome)
+            .record(elapsed.as_secs_f64());
+
+        result
+    }
+}
+
+// Injects the current OpenTelemetry context as W3C `traceparent` and
+// `tracestate` headers, so the downstream service's spans join the caller's
+// trace. Without an active span context nothing is added.
+#[cfg(feature = "otel")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TraceContextMiddleware;
+
+#[cfg(feature = "otel")]
+struct HeaderInjector<'a>(&'a mut reqwest::header::HeaderMap);
+
+#[cfg(feature = "otel")]
+impl opentelemetry::propagation::Injector for HeaderInjector<'_> {
+    // Keys come from the propagator and are always valid header names; a value
+    // that isn't a valid header is skipped rather than failing the request.
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+#[async_trait::async_trait]
+impl Middleware for TraceContextMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        use opentelemetry::propagation::TextMapPropagator;
+
+        let propagator = opentelemetry_sdk::propagation::TraceContextPropagator::new();
+        propagator.inject_context(
+            &opentelemetry::Context::current(),
+            &mut HeaderInjector(req.headers_mut()),
+        );
+        next.run(req, extensions).await
+    }
+}
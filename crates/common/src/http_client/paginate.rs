@@ -0,0 +1,51 @@
+use crate::prelude::*;
+use futures::stream::{self, Stream};
+use reqwest::header::LINK;
+
+// Follows RFC 8288 (formerly 5988) `Link: <url>; rel="next"` pagination,
+// yielding one decoded page per request until a response has no next link.
+// `request` builds the request for each page URL, so auth, query defaults and
+// the client choice stay with the caller. The stream ends after the first
+// error. Relative links are resolved against the page that returned them.
+pub fn paginate<T, F, B>(first: Url, request: F) -> impl Stream<Item = AResult<T>>
+where
+    T: serde::de::DeserializeOwned,
+    F: FnMut(&Url) -> B,
+    B: RequestBuilderExt,
+{
+    stream::unfold((Some(first), request), |(next, mut request)| async move {
+        let url = next?;
+        match request(&url).take_data_with_headers::<T>().await {
+            Ok((headers, page)) => {
+                let next = next_link(&headers).and_then(|link| url.join(&link).ok());
+                Some((Ok(page), (next, request)))
+            }
+            Err(err) => Some((Err(err), (None, request))),
+        }
+    })
+}
+
+// The `rel="next"` target of a `Link` header, if any. `rel` may hold several
+// space-separated relation types and may be unquoted.
+pub fn next_link(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let (target, params) = link.split_once(';')?;
+            let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+            let is_next = params.split(';').any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("rel=")
+                    .map(|rel| rel.trim_matches('"'))
+                    .is_some_and(|rel| {
+                        rel.split_whitespace()
+                            .any(|r| r.eq_ignore_ascii_case("next"))
+                    })
+            });
+            is_next.then(|| target.to_string())
+        })
+}
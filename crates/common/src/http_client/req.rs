@@ -0,0 +1,92 @@
+use crate::prelude::*;
+use reqwest::{
+    header::{HeaderName, HeaderValue, CONTENT_TYPE},
+    Method,
+};
+
+// A method-agnostic request builder that funnels every verb through
+// `take_data`. Errors from `url`, `header` and `json` are deferred until
+// `send_take`, so the chain itself never needs `?`.
+pub struct Req {
+    client: RequestClient,
+    method: Method,
+    url: Option<Url>,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+    error: Option<anyhow::Error>,
+}
+
+impl Req {
+    pub fn new(client: &RequestClient) -> Self {
+        Self {
+            client: client.clone(),
+            method: Method::GET,
+            url: None,
+            headers: HeaderMap::new(),
+            body: None,
+            error: None,
+        }
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn url(mut self, url: Url) -> Self {
+        self.url = Some(url);
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let parsed = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|err| aerr!("Invalid header name {name:?}: {err}"))
+            .and_then(|header| {
+                HeaderValue::from_str(value)
+                    .map(|value| (header, value))
+                    .map_err(|err| aerr!("Invalid value for header {name:?}: {err}"))
+            });
+        match parsed {
+            Ok((name, value)) => {
+                self.headers.insert(name, value);
+            }
+            Err(err) => self.fail(err),
+        }
+        self
+    }
+
+    pub fn json<B: Serialize + ?Sized>(mut self, body: &B) -> Self {
+        match serde_json::to_vec(body) {
+            Ok(bytes) => {
+                self.headers
+                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                self.body = Some(bytes);
+            }
+            Err(err) => self.fail(err.into()),
+        }
+        self
+    }
+
+    pub async fn send_take<T>(self) -> AResult<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let url = self
+            .url
+            .ok_or_else(|| aerr!("Req::send_take called without a url"))?;
+
+        let mut builder = self.client.request(self.method, url).headers(self.headers);
+        if let Some(body) = self.body {
+            builder = builder.body(body);
+        }
+        builder.take_data().await
+    }
+
+    // Keeps the first error; later ones are usually consequences of it.
+    fn fail(&mut self, err: anyhow::Error) {
+        self.error.get_or_insert(err);
+    }
+}
@@ -0,0 +1,66 @@
+use super::from_reqwest;
+use crate::prelude::*;
+use std::time::Duration;
+
+// Whether a failed request is worth repeating. Transient failures are: timeouts,
+// failures to reach the server (on WASM a blocked or dropped `fetch`, which the
+// browser reports the same way for network and CORS errors), 5xx, 408 and 429.
+// Other 4xx are definite answers, and body/decode errors would repeat. Native
+// clients get this from the retry middleware; WASM has none, hence the name,
+// but it is plain logic and behaves the same on both.
+pub fn is_retryable_wasm(err: &reqwest::Error) -> bool {
+    if let Some(status) = err.status() {
+        return status.is_server_error()
+            || status == reqwest::StatusCode::REQUEST_TIMEOUT
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+    }
+    if err.is_timeout() {
+        return true;
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    if err.is_connect() {
+        return true;
+    }
+    err.is_request() && !err.is_body() && !err.is_decode() && !err.is_builder()
+}
+
+// Sends the request built by `request` up to `attempts` times (at least
+// once), retrying the failures `is_retryable_wasm` accepts after `base_delay`,
+// doubled each time. Error statuses count as failures, so a returned response
+// is never 4xx/5xx.
+pub async fn send_with_retry<F>(
+    mut request: F,
+    attempts: u32,
+    base_delay: Duration,
+) -> AResult<Response>
+where
+    F: FnMut() -> RequestBuilder,
+{
+    let mut delay = base_delay;
+    let mut remaining = attempts;
+    loop {
+        remaining = remaining.saturating_sub(1);
+        let result = match request().send().await {
+            Ok(response) => response.error_for_status(),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(response) => return Ok(response),
+            Err(err) if remaining > 0 && is_retryable_wasm(&err) => {
+                sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+            Err(err) => return Err(from_reqwest(err)),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(super) async fn sleep(delay: Duration) {
+    gloo_timers::future::sleep(delay).await;
+}
@@ -0,0 +1,76 @@
+use crate::prelude::*;
+use dashmap::DashMap;
+use futures::future::{FutureExt, Shared};
+use std::sync::Arc;
+
+// Every awaiter of a failed flight gets the same shared error; it derefs to
+// `anyhow::Error`, so `err.downcast_ref::<CommonError>()` works for all of them.
+pub type FlightError = Arc<anyhow::Error>;
+
+cfg_if! {
+    if #[cfg(not(target_arch = "wasm32"))] {
+        type FlightFuture<T> = futures::future::BoxFuture<'static, Result<T, FlightError>>;
+
+        // What a flight can yield: shared across threads, so `Send + Sync`.
+        pub trait FlightValue: serde::de::DeserializeOwned + Clone + Send + Sync + 'static {}
+        impl<T: serde::de::DeserializeOwned + Clone + Send + Sync + 'static> FlightValue for T {}
+
+        fn start_flight<T: FlightValue>(request: reqwest_middleware::RequestBuilder) -> FlightFuture<T> {
+            super::take_data_send::<T>(request).map(|result| result.map_err(Arc::new)).boxed()
+        }
+    } else {
+        // The browser is single-threaded, and its fetch futures aren't `Send`.
+        type FlightFuture<T> = futures::future::LocalBoxFuture<'static, Result<T, FlightError>>;
+
+        pub trait FlightValue: serde::de::DeserializeOwned + Clone + 'static {}
+        impl<T: serde::de::DeserializeOwned + Clone + 'static> FlightValue for T {}
+
+        fn start_flight<T: FlightValue>(request: RequestBuilder) -> FlightFuture<T> {
+            async move { request.take_data::<T>().await.map_err(Arc::new) }.boxed_local()
+        }
+    }
+}
+
+type Flight<T> = Shared<FlightFuture<T>>;
+
+// Coalesces concurrent GETs of the same URL: while a request is in flight,
+// later callers await it instead of hitting the network, and every awaiter gets
+// a clone of the parsed result. Nothing is cached; once the flight lands the
+// next call starts a fresh request. On native it is `Send + Sync`, so clones
+// can be moved into tasks spawned on a multi-thread runtime.
+pub struct SingleFlight<T: FlightValue> {
+    client: RequestClient,
+    inflight: Arc<DashMap<String, Flight<T>>>,
+}
+
+impl<T: FlightValue> SingleFlight<T> {
+    pub fn new(client: &RequestClient) -> Self {
+        Self {
+            client: client.clone(),
+            inflight: Arc::new(DashMap::new()),
+        }
+    }
+
+    pub async fn get(&self, url: &str) -> Result<T, FlightError> {
+        let flight = self
+            .inflight
+            .entry(url.to_string())
+            .or_insert_with(|| start_flight(self.client.get(url)).shared())
+            .clone();
+
+        let result = flight.clone().await;
+        // Only retire our own flight, never one a later caller has started.
+        self.inflight
+            .remove_if(url, |_, current| current.ptr_eq(&flight));
+        result
+    }
+}
+
+impl<T: FlightValue> Clone for SingleFlight<T> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            inflight: Arc::clone(&self.inflight),
+        }
+    }
+}
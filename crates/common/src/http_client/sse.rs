@@ -0,0 +1,90 @@
+use crate::prelude::*;
+use futures::stream::{self, LocalBoxStream, StreamExt};
+use std::collections::VecDeque;
+
+pub type SseStream = LocalBoxStream<'static, AResult<SseEvent>>;
+
+// One dispatched Server-Sent Event. `event` is `None` for the default
+// "message" type; multi-line `data:` fields are joined with `\n`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+    pub id: Option<String>,
+}
+
+// Incremental `text/event-stream` parser. Bytes are buffered until a full line
+// arrives, so events and UTF-8 sequences may be split across chunks freely.
+#[derive(Default)]
+struct SseParser {
+    pending: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl SseParser {
+    fn feed(&mut self, chunk: &[u8], out: &mut VecDeque<SseEvent>) {
+        self.pending.extend_from_slice(chunk);
+        while let Some(end) = self.pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+            if let Some(event) = self.line(line) {
+                out.push_back(event);
+            }
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            // `retry` and unknown fields are ignored.
+            _ => {}
+        }
+        None
+    }
+
+    // An event without any `data:` line is dropped, as browsers do.
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let id = self.id.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        let data = std::mem::take(&mut self.data).join("\n");
+        Some(SseEvent { event, data, id })
+    }
+}
+
+// Turns a `text/event-stream` response into parsed events. The stream ends
+// with the body; a trailing event without its blank line is discarded.
+pub(crate) fn sse_stream(response: Response) -> SseStream {
+    let state = (
+        response.bytes_stream().boxed(),
+        SseParser::default(),
+        VecDeque::new(),
+    );
+    stream::unfold(state, |(mut body, mut parser, mut ready)| async move {
+        loop {
+            if let Some(event) = ready.pop_front() {
+                return Some((Ok(event), (body, parser, ready)));
+            }
+            match body.next().await? {
+                Ok(chunk) => parser.feed(&chunk, &mut ready),
+                Err(err) => return Some((Err(err.into()), (body, parser, ready))),
+            }
+        }
+    })
+    .boxed_local()
+}
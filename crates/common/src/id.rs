@@ -0,0 +1,19 @@
+use crate::prelude::*;
+
+pub fn new_uuid() -> Uuid {
+    Uuid::new_v4()
+}
+
+// 22 characters or fewer instead of the 36 of the hyphenated form.
+pub fn uuid_to_base58(uuid: &Uuid) -> String {
+    bs58::encode(uuid.as_bytes()).into_string()
+}
+
+pub fn uuid_from_base58(input: &str) -> AResult<Uuid> {
+    let bytes = bs58::decode(input).into_vec()?;
+    let bytes: [u8; 16] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| aerr!("Expected 16 bytes for a UUID, got {}", bytes.len()))?;
+    Ok(Uuid::from_bytes(bytes))
+}
@@ -0,0 +1,81 @@
+use crate::prelude::*;
+use anyhow::Context;
+use serde_json::Value;
+
+// How much of the input a parse error quotes.
+const SNIPPET_CHARS: usize = 64;
+
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> AResult<String> {
+    serde_json::to_string(value)
+        .with_context(|| format!("Failed to serialize {} to JSON", std::any::type_name::<T>()))
+}
+
+pub fn to_json_pretty<T: Serialize + ?Sized>(value: &T) -> AResult<String> {
+    serde_json::to_string_pretty(value)
+        .with_context(|| format!("Failed to serialize {} to JSON", std::any::type_name::<T>()))
+}
+
+// Parse errors name the target type and quote the start of the input, so a
+// failure in a log line is actionable without the full payload.
+pub fn from_json<T: serde::de::DeserializeOwned>(input: &str) -> AResult<T> {
+    serde_json::from_str(input).with_context(|| {
+        format!(
+            "Failed to parse {} from JSON {:?}",
+            std::any::type_name::<T>(),
+            snippet(input)
+        )
+    })
+}
+
+pub(crate) fn snippet(input: &str) -> String {
+    let mut chars = input.chars();
+    let head: String = chars.by_ref().take(SNIPPET_CHARS).collect();
+    if chars.next().is_some() {
+        format!("{head}...")
+    } else {
+        head
+    }
+}
+
+// Deterministic JSON for signing, in the spirit of RFC 8785 (JCS): object keys
+// sorted by their UTF-16 code units, no insignificant whitespace. Numbers use
+// `serde_json`'s shortest round-trip formatting, which matches JCS for the
+// integers and finite floats typical of signed payloads.
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> AResult<Vec<u8>> {
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> AResult<()> {
+    match value {
+        Value::Array(items) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(entries) => {
+            let mut entries: Vec<_> = entries.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            out.push(b'{');
+            for (index, (key, item)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_canonical(item, out)?;
+            }
+            out.push(b'}');
+        }
+        scalar => serde_json::to_writer(&mut *out, scalar)?,
+    }
+    Ok(())
+}
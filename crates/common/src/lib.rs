@@ -1,6 +1,19 @@
+pub mod b58;
+pub mod env_var;
+pub mod error;
 pub mod http_client;
+#[cfg(feature = "uuid")]
+pub mod id;
+pub mod json;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod local_date_time;
+pub mod percent;
 pub mod prelude;
+#[cfg(feature = "qr")]
+pub mod qr;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod retry;
 #[cfg(feature = "scalar")]
 pub mod scalar;
+#[cfg(feature = "hash")]
+pub mod token;
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+// Everything but RFC 3986 unreserved characters, so the result is safe as a
+// path segment or a query key/value alike. `Url` leaves more characters bare
+// in full URLs; this is the conservative set for a single component.
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+pub fn percent_encode(input: &str) -> String {
+    utf8_percent_encode(input, COMPONENT).to_string()
+}
+
+// Unlike `percent_decode_str`, a `%` that isn't followed by two hex digits is
+// an error rather than being passed through, as is invalid UTF-8.
+pub fn percent_decode(input: &str) -> AResult<String> {
+    let bytes = input.as_bytes();
+    if let Some(index) = bytes.iter().enumerate().position(|(index, &byte)| {
+        byte == b'%'
+            && !bytes
+                .get(index + 1..index + 3)
+                .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    }) {
+        bail!("Invalid percent-encoding at byte {index} of {input:?}");
+    }
+    let decoded = percent_decode_str(input)
+        .decode_utf8()
+        .map_err(|err| aerr!("Percent-decoded {input:?} is not valid UTF-8: {err}"))?;
+    Ok(decoded.into_owned())
+}
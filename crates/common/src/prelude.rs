@@ -1,5 +1,32 @@
+pub use crate::b58::*;
+pub use crate::env_var::*;
+pub use crate::error::*;
 pub use crate::http_client::*;
+#[cfg(feature = "uuid")]
+pub use crate::id::*;
+pub use crate::json::*;
+pub use crate::percent::*;
+#[cfg(feature = "qr")]
+pub use crate::qr::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use crate::retry::*;
+#[cfg(feature = "scalar")]
 pub use crate::scalar::*;
+#[cfg(feature = "hash")]
+pub use crate::token::*;
+
+// The framed base58 stream format, as `prelude::b58`.
+pub mod b58 {
+    pub use crate::b58::*;
+}
+
+// The crypto surface on its own (curve types, `PubRing`, the conversion
+// traits and every helper in `crate::scalar`), for code that wants
+// `use common_core::prelude::scalar::*` without the rest of the prelude.
+#[cfg(feature = "scalar")]
+pub mod scalar {
+    pub use crate::scalar::*;
+}
 
 pub use toml;
 
@@ -130,25 +157,65 @@ pub mod base58 {
 
         Ok(res)
     }
+
+    // `#[serde(with = "base58::option")]` for `Option<Vec<u8>>`: `None` is
+    // JSON `null`, `Some` a base58 string. Add `#[serde(default)]` as well to
+    // accept a missing field.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S>(bytes: &Option<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match bytes {
+                Some(bytes) => super::serialize(bytes, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let req: Option<String> = serde::Deserialize::deserialize(deserializer)?;
+            req.map(|req| {
+                bs58::decode(req)
+                    .into_vec()
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))
+            })
+            .transpose()
+        }
+    }
 }
 
 // pub use base64::engine::general_purpose::GeneralPurpose::encode as bs64encode;
 pub use base64::engine::general_purpose::STANDARD as BS64ENGINE;
 pub use base64::Engine as Base64Engine;
-use image::ImageFormat;
-use image::Luma;
-use qrcode::QrCode;
-use std::io::Cursor;
-
-pub fn data_to_qr_png(data: &[u8]) -> AResult<String> {
-    let code = QrCode::new(data)?;
-
-    // Render the bits into an image.
-    let luma_image = code.render::<Luma<u8>>().build();
-    let mut png_bytes: Vec<u8> = Vec::new();
-    let mut cursor = Cursor::new(&mut png_bytes);
-    luma_image.write_to(&mut cursor, ImageFormat::Png)?;
-    // let base64_encoded = bs64encode(png_bytes);
-    let base64_encoded = BS64ENGINE.encode(png_bytes);
-    Ok(format!("data:image/png;base64,{}", base64_encoded))
+
+// Small byte primitives for crypto code: `use common_core::prelude::bytes`.
+pub mod bytes {
+    use super::*;
+    use subtle::ConstantTimeEq;
+
+    // Constant-time in the contents; slices of different lengths compare
+    // unequal immediately, since lengths are rarely secret.
+    pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+        a.ct_eq(b).into()
+    }
+
+    // XORs `src` into `dst` byte by byte.
+    pub fn xor_into(dst: &mut [u8], src: &[u8]) -> AResult<()> {
+        if dst.len() != src.len() {
+            bail!(
+                "xor_into length mismatch: dst is {} bytes, src is {}",
+                dst.len(),
+                src.len()
+            );
+        }
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d ^= s;
+        }
+        Ok(())
+    }
 }
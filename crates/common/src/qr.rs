@@ -0,0 +1,143 @@
+use crate::prelude::*;
+use image::ImageFormat;
+use image::{Luma, Rgb};
+use qrcode::QrCode;
+use std::io::Cursor;
+
+pub use qrcode::EcLevel;
+
+// Rendering parameters for `render_qr_png`. The defaults reproduce the
+// classic black-on-white code with 8-pixel modules and a quiet zone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrOptions {
+    pub ecc: EcLevel,
+    // Side length of one module, in pixels.
+    pub scale: u32,
+    pub quiet_zone: bool,
+    // RGB color of dark modules.
+    pub dark_color: [u8; 3],
+    // RGB color of light modules and the quiet zone.
+    pub light_color: [u8; 3],
+}
+
+impl Default for QrOptions {
+    fn default() -> Self {
+        Self {
+            ecc: EcLevel::M,
+            scale: 8,
+            quiet_zone: true,
+            dark_color: [0, 0, 0],
+            light_color: [255, 255, 255],
+        }
+    }
+}
+
+pub fn data_to_qr_png(data: &[u8]) -> AResult<String> {
+    render_qr_png(data, &QrOptions::default())
+}
+
+// Renders `data` as a PNG data-URL. Grayscale colors produce a single-channel
+// PNG; anything else is rendered as RGB.
+pub fn render_qr_png(data: &[u8], opts: &QrOptions) -> AResult<String> {
+    if opts.scale == 0 {
+        bail!("QR scale must be greater than zero");
+    }
+    let code = QrCode::with_error_correction_level(data, opts.ecc)?;
+
+    let mut png_bytes: Vec<u8> = Vec::new();
+    let mut cursor = Cursor::new(&mut png_bytes);
+    let is_gray = |[r, g, b]: [u8; 3]| r == g && g == b;
+    if is_gray(opts.dark_color) && is_gray(opts.light_color) {
+        code.render::<Luma<u8>>()
+            .dark_color(Luma([opts.dark_color[0]]))
+            .light_color(Luma([opts.light_color[0]]))
+            .quiet_zone(opts.quiet_zone)
+            .module_dimensions(opts.scale, opts.scale)
+            .build()
+            .write_to(&mut cursor, ImageFormat::Png)?;
+    } else {
+        code.render::<Rgb<u8>>()
+            .dark_color(Rgb(opts.dark_color))
+            .light_color(Rgb(opts.light_color))
+            .quiet_zone(opts.quiet_zone)
+            .module_dimensions(opts.scale, opts.scale)
+            .build()
+            .write_to(&mut cursor, ImageFormat::Png)?;
+    }
+    let base64_encoded = BS64ENGINE.encode(png_bytes);
+    Ok(format!("data:image/png;base64,{}", base64_encoded))
+}
+
+// Async variant of `data_to_qr_png` that runs the CPU-bound encoding on
+// Tokio's blocking pool, so it does not stall the runtime. On WASM there is
+// no blocking pool and it simply runs inline.
+pub async fn data_to_qr_png_async(data: &[u8]) -> AResult<String> {
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            data_to_qr_png(data)
+        } else {
+            let data = data.to_vec();
+            tokio::task::spawn_blocking(move || data_to_qr_png(&data)).await?
+        }
+    }
+}
+
+// Encodes many payloads on Tokio's blocking pool, at most one per available
+// CPU at a time, returning the data-URLs in input order. The first failing
+// item fails the whole batch, naming its index.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn data_to_qr_png_batch(items: &[Vec<u8>]) -> AResult<Vec<String>> {
+    use futures::stream::{self, StreamExt, TryStreamExt};
+
+    let concurrency = std::thread::available_parallelism().map_or(1, |n| n.get());
+    stream::iter(items.iter().cloned().enumerate())
+        .map(|(index, item)| async move {
+            tokio::task::spawn_blocking(move || data_to_qr_png(&item))
+                .await
+                .map_err(|err| {
+                    let what = if err.is_panic() {
+                        "panicked"
+                    } else {
+                        "was cancelled"
+                    };
+                    aerr!("QR item {index} {what}: {err}")
+                })?
+                .map_err(|err| aerr!("QR item {index} failed: {err}"))
+        })
+        .buffered(concurrency)
+        .try_collect()
+        .await
+}
+
+// Splits `data` into QR-sized frames, each prefixed with a 2-byte
+// `[index, total]` header so a scanner can reassemble them in order. At most
+// 255 frames can be addressed by the header.
+pub fn split_qr_payload(data: &[u8], chunk_bytes: usize) -> AResult<Vec<Vec<u8>>> {
+    if chunk_bytes == 0 {
+        bail!("chunk_bytes must be greater than zero");
+    }
+    let total = data.len().div_ceil(chunk_bytes).max(1);
+    let total = u8::try_from(total)
+        .map_err(|_| aerr!("{total} chunks exceed the 255-chunk header limit"))?;
+
+    let frames = (0..total)
+        .map(|index| {
+            let start = index as usize * chunk_bytes;
+            let end = (start + chunk_bytes).min(data.len());
+            let mut frame = Vec::with_capacity(2 + end - start);
+            frame.extend_from_slice(&[index, total]);
+            frame.extend_from_slice(&data[start..end]);
+            frame
+        })
+        .collect();
+
+    Ok(frames)
+}
+
+// Renders each frame produced by `split_qr_payload` as a PNG data-URL.
+pub fn data_to_qr_png_chunked(data: &[u8], chunk_bytes: usize) -> AResult<Vec<String>> {
+    split_qr_payload(data, chunk_bytes)?
+        .iter()
+        .map(|frame| data_to_qr_png(frame))
+        .collect()
+}
@@ -0,0 +1,44 @@
+use crate::prelude::*;
+use std::future::Future;
+use std::time::Duration;
+pub use tokio_util::sync::CancellationToken;
+
+// Runs `operation` up to `attempts` times (at least once), doubling the wait
+// after each failure starting from `base_delay`, and returns the last error if
+// every attempt fails. A cancelled `cancel` token cuts the wait short and
+// yields `CommonError::Cancelled`; an attempt already running is not aborted.
+pub async fn retry_async<T, F, Fut>(
+    attempts: u32,
+    base_delay: Duration,
+    cancel: Option<&CancellationToken>,
+    mut operation: F,
+) -> AResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AResult<T>>,
+{
+    let attempts = attempts.max(1);
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        if cancel.is_some_and(CancellationToken::is_cancelled) {
+            return Err(CommonError::Cancelled.into());
+        }
+        let err = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt >= attempts => return Err(err),
+            Err(err) => err,
+        };
+        tracing::debug!("Attempt {attempt}/{attempts} failed, retrying in {delay:?}: {err:#}");
+
+        match cancel {
+            Some(cancel) => tokio::select! {
+                _ = cancel.cancelled() => return Err(CommonError::Cancelled.into()),
+                _ = tokio::time::sleep(delay) => {}
+            },
+            None => tokio::time::sleep(delay).await,
+        }
+        delay = delay.saturating_mul(2);
+        attempt += 1;
+    }
+}
@@ -1,38 +1,280 @@
-use crate::prelude::AResult;
-use anyhow::anyhow;
+use crate::prelude::{to_canonical_json, AResult, Serialize};
+use anyhow::{anyhow, bail};
+use curve25519_dalek::traits::Identity;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use subtle::ConstantTimeEq;
 
 pub use curve25519_dalek::{
-    constants::RISTRETTO_BASEPOINT_POINT,
+    constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE},
     ristretto::CompressedRistretto,
+    ristretto::RistrettoBasepointTable,
     ristretto::RistrettoPoint,
     // self,
     scalar::Scalar,
 };
 
+mod aggregate;
+mod elgamal;
+mod key_image;
+mod keypair;
+mod merkle;
+mod pedersen;
+mod schnorr;
+#[cfg(feature = "zeroize")]
+mod secret;
+mod shamir;
+#[cfg(feature = "test-util")]
+mod test_util;
+pub use aggregate::*;
+pub use elgamal::*;
+pub use key_image::*;
+pub use keypair::*;
+pub use merkle::*;
+pub use pedersen::*;
+pub use schnorr::*;
+#[cfg(feature = "zeroize")]
+pub use secret::*;
+pub use shamir::*;
+#[cfg(feature = "test-util")]
+pub use test_util::*;
+
 pub type PubRing = Vec<RistrettoPoint>;
 
+// A ring kept in compressed form, so decompression (and validation) can be
+// deferred until the points are actually used.
+pub type CompressedPubRing = Vec<[u8; 32]>;
+
+pub fn compress_point(point: &RistrettoPoint) -> [u8; 32] {
+    point.compress().to_bytes()
+}
+
+pub fn try_decompress(bytes: &[u8; 32]) -> AResult<RistrettoPoint> {
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or_else(|| anyhow!("Bytes {bytes:?} do not represent a valid Ristretto point"))
+}
+
+// Ristretto is a prime-order group: every element except the identity
+// generates the whole group, so there are no small-order points to filter
+// and no cofactor to clear (unlike raw Edwards25519). The identity is the one
+// degenerate key left, e.g. it makes `s * G == R + e * P` independent of `e`.
+// Run untrusted keys through this before accepting them.
+pub fn reject_identity(point: &RistrettoPoint) -> AResult<()> {
+    if *point == RistrettoPoint::identity() {
+        bail!("The identity point is not an acceptable key");
+    }
+    Ok(())
+}
+
+// Cheap pre-filter for untrusted point bytes: a canonical Ristretto encoding is
+// a field element below `p = 2^255 - 19` whose low bit (sign) is clear. This is
+// necessary but not sufficient; `try_decompress` still has to run the square
+// root check, but obviously malformed input can be rejected without it.
+pub fn is_canonical_encoding(bytes: &[u8; 32]) -> bool {
+    if bytes[0] & 1 == 1 || bytes[31] & 0x80 != 0 {
+        return false;
+    }
+    // Below 2^255, the only values >= p are 0x7fff..ffed through 0x7fff..ffff.
+    let at_least_p =
+        bytes[31] == 0x7f && bytes[1..31].iter().all(|&byte| byte == 0xff) && bytes[0] >= 0xed;
+    !at_least_p
+}
+
+pub fn scalar_from_u128(n: u128) -> Scalar {
+    Scalar::from(n)
+}
+
+// Little-endian bytes of a canonical scalar (< the group order `l`).
+// Non-canonical encodings are rejected rather than silently reduced.
+pub fn scalar_from_canonical_bytes(bytes: [u8; 32]) -> AResult<Scalar> {
+    Option::from(Scalar::from_canonical_bytes(bytes))
+        .ok_or_else(|| anyhow!("Bytes {bytes:?} are not a canonical scalar encoding"))
+}
+
+pub fn scalar_from_le_bytes(bytes: [u8; 32]) -> AResult<Scalar> {
+    scalar_from_canonical_bytes(bytes)
+}
+
+// Reduces any 32 bytes modulo `l`. Only for inputs that are meant to be
+// reduced (hash output, say); key material should use the strict variant.
+pub fn scalar_from_bytes_mod_order(bytes: [u8; 32]) -> Scalar {
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+// Inverts every scalar in place with Montgomery's trick (one inversion plus
+// about 3n multiplications) and returns the product of the original values.
+// Zero has no inverse, so any zero element is an error and leaves the slice
+// untouched.
+pub fn batch_invert(scalars: &mut [Scalar]) -> AResult<Scalar> {
+    if let Some(index) = scalars.iter().position(|scalar| *scalar == Scalar::ZERO) {
+        bail!("Cannot invert zero scalar at index {index}");
+    }
+    let product = scalars.iter().product();
+    Scalar::batch_invert(scalars);
+    Ok(product)
+}
+
 pub trait PublicKeyComputable {
     fn compute_pubkey(&self) -> RistrettoPoint;
 }
 
 impl PublicKeyComputable for Scalar {
     fn compute_pubkey(&self) -> RistrettoPoint {
-        self * RISTRETTO_BASEPOINT_POINT
+        mul_base(self)
     }
 }
 
+pub fn compute_pubkeys(scalars: &[Scalar]) -> PubRing {
+    scalars.iter().map(mul_base).collect()
+}
+
+// Same as `compute_pubkeys`, spread across rayon's pool; order is preserved.
+#[cfg(feature = "rayon")]
+pub fn compute_pubkeys_par(scalars: &[Scalar]) -> PubRing {
+    use rayon::prelude::*;
+
+    scalars.par_iter().map(mul_base).collect()
+}
+
+// Canonical ring order: ascending by compressed encoding, so every party
+// derives the same order (and the same Merkle root or aggregate key) from the
+// same set of keys.
+pub fn sort_pubring(ring: &PubRing) -> PubRing {
+    let mut keyed: Vec<([u8; 32], RistrettoPoint)> = ring
+        .iter()
+        .map(|point| (compress_point(point), *point))
+        .collect();
+    keyed.sort_by_key(|(bytes, _)| *bytes);
+    keyed.into_iter().map(|(_, point)| point).collect()
+}
+
+// Keys of `a` that are not in `b`, e.g. current keys minus revoked ones.
+// Membership is by canonical encoding (`PointKey`); `a`'s order is kept.
+pub fn pubring_difference(a: &PubRing, b: &PubRing) -> PubRing {
+    let b: HashSet<PointKey> = b.iter().map(PointKey::from).collect();
+    a.iter()
+        .filter(|point| !b.contains(&PointKey::from(*point)))
+        .copied()
+        .collect()
+}
+
+// Keys of `a` that are also in `b`, in `a`'s order.
+pub fn pubring_intersection(a: &PubRing, b: &PubRing) -> PubRing {
+    let b: HashSet<PointKey> = b.iter().map(PointKey::from).collect();
+    a.iter()
+        .filter(|point| b.contains(&PointKey::from(*point)))
+        .copied()
+        .collect()
+}
+
+// Checks that a stored public key really belongs to `secret`. The comparison
+// is constant-time so it does not leak how much of the claimed key matched.
+pub fn pubkey_matches(secret: &Scalar, claimed_public: &RistrettoPoint) -> bool {
+    secret.compute_pubkey().ct_eq(claimed_public).into()
+}
+
+// Verifies that `ring[i]` is the public key of `secrets[i]` for every index,
+// reporting the first mismatch.
+pub fn verify_pubring(secrets: &[Scalar], ring: &PubRing) -> AResult<()> {
+    if secrets.len() != ring.len() {
+        return Err(anyhow!(
+            "Ring has {} keys but {} secrets were given",
+            ring.len(),
+            secrets.len()
+        ));
+    }
+    match secrets
+        .iter()
+        .zip(ring)
+        .position(|(secret, public)| !pubkey_matches(secret, public))
+    {
+        Some(index) => Err(anyhow!(
+            "Ring key at index {index} does not match its secret"
+        )),
+        None => Ok(()),
+    }
+}
+
+// A stable 16-byte identifier for indexing points, taken from the first half
+// of SHA-256 over the compressed encoding. 128 bits keeps accidental
+// collisions negligible for indexing, but it is not a commitment: use the full
+// 32-byte encoding wherever an adversary could benefit from a collision.
+pub fn point_id(point: &RistrettoPoint) -> [u8; 16] {
+    let digest = Sha256::digest(point.compress().as_bytes());
+    let mut id = [0u8; 16];
+    id.copy_from_slice(&digest[..16]);
+    id
+}
+
+pub fn point_id_str(point: &RistrettoPoint) -> String {
+    bs58::encode(point_id(point)).into_string()
+}
+
+// A uniformly random scalar from the OS RNG, reduced from 64 bytes so the
+// result carries no modular bias.
+pub fn random_scalar() -> AResult<Scalar> {
+    let mut wide = [0u8; 64];
+    getrandom::getrandom(&mut wide).map_err(|err| anyhow!("OS RNG failed: {err}"))?;
+    Ok(Scalar::from_bytes_mod_order_wide(&wide))
+}
+
+// Hashes `domain` followed by `parts` with SHA-512 and reduces the wide digest
+// into a scalar, giving a uniformly distributed challenge/coefficient.
+pub(crate) fn hash_to_scalar(domain: &[u8], parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(domain);
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize().into())
+}
+
+// Fixed-base multiplication `k * G` through dalek's precomputed basepoint
+// table, which is several times faster than multiplying the point directly.
+pub fn mul_base(k: &Scalar) -> RistrettoPoint {
+    k * RISTRETTO_BASEPOINT_TABLE
+}
+
+// Group operations for accumulators, so callers don't need dalek's operator
+// impls in scope. The identity is `point_sub(p, p)`.
+pub fn point_add(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+    a + b
+}
+
+pub fn point_sub(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+    a - b
+}
+
+pub fn point_neg(a: &RistrettoPoint) -> RistrettoPoint {
+    -a
+}
+
 pub trait LocalByteConvertible {
-    fn to_bytes(&self) -> [u8; 32];
+    // The fixed-size wire form: `[u8; 32]` for points, `[u8; 64]` for signatures.
+    type Bytes: AsRef<[u8]>;
+
+    fn to_bytes(&self) -> Self::Bytes;
     fn from_bytes(bytes: &[u8]) -> AResult<Self>
     where
         Self: Sized;
     fn to_base58(&self) -> String;
-    fn from_base58(input: String) -> AResult<Self>
+    fn from_base58_str(input: &str) -> AResult<Self>
     where
         Self: Sized;
+    fn from_base58(input: String) -> AResult<Self>
+    where
+        Self: Sized,
+    {
+        Self::from_base58_str(&input)
+    }
 }
 
 impl LocalByteConvertible for RistrettoPoint {
+    type Bytes = [u8; 32];
+
     fn to_bytes(&self) -> [u8; 32] {
         self.compress().to_bytes()
     }
@@ -50,9 +292,141 @@ impl LocalByteConvertible for RistrettoPoint {
         bs58::encode(self.to_bytes()).into_string()
     }
 
-    fn from_base58(input: String) -> AResult<Self> {
+    fn from_base58_str(input: &str) -> AResult<Self> {
+        let bytes = bs58::decode(input).into_vec()?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+// Base58 straight from the compressed form, skipping the decompress and
+// recompress round trip. Decoding only checks the length: the result may not
+// be a valid point, see `compressed_from_base58_checked`.
+impl LocalByteConvertible for CompressedRistretto {
+    type Bytes = [u8; 32];
+
+    fn to_bytes(&self) -> [u8; 32] {
+        CompressedRistretto::to_bytes(self)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> AResult<Self> {
+        CompressedRistretto::from_slice(bytes)
+            .map_err(|_| anyhow!("Expected 32-byte compressed point, got {}", bytes.len()))
+    }
+
+    fn to_base58(&self) -> String {
+        bs58::encode(self.as_bytes()).into_string()
+    }
+
+    fn from_base58_str(input: &str) -> AResult<Self> {
         let bytes = bs58::decode(input).into_vec()?;
 
         Self::from_bytes(&bytes)
     }
 }
+
+// Like `CompressedRistretto::from_base58_str`, but also checks that the bytes
+// decompress to a valid point, while still returning the compressed form.
+pub fn compressed_from_base58_checked(input: &str) -> AResult<CompressedRistretto> {
+    let compressed = CompressedRistretto::from_base58_str(input)?;
+    try_decompress(compressed.as_bytes())?;
+    Ok(compressed)
+}
+
+// Decodes a base58 public key typed or pasted by a user, reporting a wrong
+// length directly instead of as a generic decompression failure.
+pub fn pubkey_from_base58(input: &str) -> AResult<RistrettoPoint> {
+    let bytes = bs58::decode(input.trim())
+        .into_vec()
+        .map_err(|err| anyhow!("Invalid base58 pubkey: {err}"))?;
+    let bytes: [u8; 32] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow!("Expected 32-byte pubkey, got {}", bytes.len()))?;
+    try_decompress(&bytes)
+}
+
+// Base58 text form of a point. `Display` can't be implemented on the foreign
+// `RistrettoPoint` itself, so CLI code prints and parses keys through this.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Point58(RistrettoPoint);
+
+impl Point58 {
+    pub fn point(&self) -> RistrettoPoint {
+        self.0
+    }
+}
+
+impl From<RistrettoPoint> for Point58 {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(point)
+    }
+}
+
+impl From<Point58> for RistrettoPoint {
+    fn from(point: Point58) -> Self {
+        point.0
+    }
+}
+
+impl fmt::Display for Point58 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.to_base58())
+    }
+}
+
+impl FromStr for Point58 {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> AResult<Self> {
+        RistrettoPoint::from_base58_str(input).map(Self)
+    }
+}
+
+// A point that is known to be valid: it can only be built from a canonical
+// compressed encoding, so holders never need to re-check it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidPoint(RistrettoPoint);
+
+impl ValidPoint {
+    pub fn point(&self) -> RistrettoPoint {
+        self.0
+    }
+}
+
+impl TryFrom<[u8; 32]> for ValidPoint {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: [u8; 32]) -> AResult<Self> {
+        try_decompress(&bytes).map(Self)
+    }
+}
+
+impl TryFrom<&[u8]> for ValidPoint {
+    type Error = anyhow::Error;
+
+    fn try_from(bytes: &[u8]) -> AResult<Self> {
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Expected 32 bytes for a point, got {}", bytes.len()))?;
+        Self::try_from(bytes)
+    }
+}
+
+impl From<RistrettoPoint> for ValidPoint {
+    fn from(point: RistrettoPoint) -> Self {
+        Self(point)
+    }
+}
+
+impl std::ops::Deref for ValidPoint {
+    type Target = RistrettoPoint;
+
+    fn deref(&self) -> &RistrettoPoint {
+        &self.0
+    }
+}
+
+pub fn display_point(point: &RistrettoPoint) -> String {
+    Point58(*point).to_string()
+}
@@ -0,0 +1,30 @@
+use super::*;
+use curve25519_dalek::traits::{Identity, MultiscalarMul};
+
+const KEY_LIST_DOMAIN: &[u8] = b"common_core/musig/key-list";
+const COEFFICIENT_DOMAIN: &[u8] = b"common_core/musig/coefficient";
+
+// MuSig key aggregation: every key `X_i` gets the coefficient
+// `a_i = H(L, X_i)`, where `L` commits to the whole ring, and the aggregate is
+// `sum(a_i * X_i)`. Binding each coefficient to the full key list is what stops
+// a rogue-key attacker from choosing a key that cancels the others out.
+//
+// `L` hashes the keys in the given order, so all signers must agree on the
+// order (e.g. by sorting the ring) to derive the same aggregate key.
+pub fn aggregate_keys(keys: &PubRing) -> (RistrettoPoint, Vec<Scalar>) {
+    if keys.is_empty() {
+        return (RistrettoPoint::identity(), Vec::new());
+    }
+
+    let compressed: Vec<[u8; 32]> = keys.iter().map(compress_point).collect();
+    let key_list: Vec<&[u8]> = compressed.iter().map(|bytes| bytes.as_slice()).collect();
+    let list_hash = hash_to_scalar(KEY_LIST_DOMAIN, &key_list).to_bytes();
+
+    let coefficients: Vec<Scalar> = compressed
+        .iter()
+        .map(|key| hash_to_scalar(COEFFICIENT_DOMAIN, &[&list_hash, key]))
+        .collect();
+    let aggregate = RistrettoPoint::multiscalar_mul(&coefficients, keys);
+
+    (aggregate, coefficients)
+}
@@ -0,0 +1,27 @@
+use super::*;
+
+// An ElGamal ciphertext `(r * G, M + r * P)` for the recipient key `P`.
+pub type ElGamalCiphertext = (RistrettoPoint, RistrettoPoint);
+
+// Encrypts a message already encoded as a point under a fresh ephemeral
+// scalar, so encrypting the same message twice gives unlinkable ciphertexts.
+// Fallible because drawing that scalar from the OS RNG can fail; as with
+// `KeyPair::random` and `split_secret`, that is returned rather than panicked.
+pub fn elgamal_encrypt(
+    pubkey: &RistrettoPoint,
+    message_point: &RistrettoPoint,
+) -> AResult<ElGamalCiphertext> {
+    let ephemeral = random_scalar()?;
+    Ok((mul_base(&ephemeral), message_point + ephemeral * pubkey))
+}
+
+pub fn elgamal_decrypt(secret: &Scalar, ciphertext: ElGamalCiphertext) -> RistrettoPoint {
+    let (ephemeral_point, masked) = ciphertext;
+    masked - secret * ephemeral_point
+}
+
+// Componentwise sum: decrypts to `M1 + M2`. This homomorphism is also why
+// plain ElGamal is malleable; authenticate ciphertexts where that matters.
+pub fn elgamal_add(a: ElGamalCiphertext, b: ElGamalCiphertext) -> ElGamalCiphertext {
+    (a.0 + b.0, a.1 + b.1)
+}
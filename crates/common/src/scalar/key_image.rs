@@ -0,0 +1,44 @@
+use super::*;
+use sha3::Keccak512;
+
+// A hashable, orderable key for a point: its compressed encoding. Ristretto
+// encodings are canonical, so equal points always map to equal keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PointKey([u8; 32]);
+
+impl PointKey {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl From<&RistrettoPoint> for PointKey {
+    fn from(point: &RistrettoPoint) -> Self {
+        Self(compress_point(point))
+    }
+}
+
+impl From<RistrettoPoint> for PointKey {
+    fn from(point: RistrettoPoint) -> Self {
+        Self::from(&point)
+    }
+}
+
+// `I = x * Hp(x * G)`, the same image `BLSAG` signatures carry when signed
+// with `Keccak512`, so a wallet can match its outputs against spent images.
+pub fn key_image(secret: &Scalar) -> RistrettoPoint {
+    let public = secret.compute_pubkey();
+    secret
+        * RistrettoPoint::from_hash(Keccak512::default().chain_update(public.compress().as_bytes()))
+}
+
+pub fn key_images(secrets: &[Scalar]) -> Vec<RistrettoPoint> {
+    secrets.iter().map(key_image).collect()
+}
+
+// Index of the first image already in `seen`, i.e. the first spent output.
+pub fn any_key_image_seen(images: &[RistrettoPoint], seen: &HashSet<PointKey>) -> Option<usize> {
+    images
+        .iter()
+        .position(|image| seen.contains(&PointKey::from(image)))
+}
@@ -0,0 +1,57 @@
+use super::*;
+
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+// A secret scalar together with its public key. `Debug` shows only the
+// public half.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct KeyPair {
+    secret: Scalar,
+    public: RistrettoPoint,
+}
+
+impl KeyPair {
+    pub fn from_secret(secret: Scalar) -> Self {
+        Self {
+            secret,
+            public: secret.compute_pubkey(),
+        }
+    }
+
+    pub fn random() -> AResult<Self> {
+        Ok(Self::from_secret(random_scalar()?))
+    }
+
+    pub fn secret(&self) -> &Scalar {
+        &self.secret
+    }
+
+    pub fn public(&self) -> RistrettoPoint {
+        self.public
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("public", &self.public.to_base58())
+            .finish_non_exhaustive()
+    }
+}
+
+// Draws random key pairs until the base58 public key starts with `prefix`.
+// Each extra character multiplies the expected work by about 58, so keep
+// prefixes short; characters outside the base58 alphabet (`0`, `O`, `I`, `l`)
+// can never match and are rejected up front.
+pub fn find_vanity_keypair(prefix: &str, max_attempts: u64) -> AResult<KeyPair> {
+    if let Some(invalid) = prefix.chars().find(|c| !BASE58_ALPHABET.contains(*c)) {
+        bail!("Vanity prefix {prefix:?} contains non-base58 character {invalid:?}");
+    }
+    for _ in 0..max_attempts {
+        let keypair = KeyPair::random()?;
+        if keypair.public.to_base58().starts_with(prefix) {
+            return Ok(keypair);
+        }
+    }
+    bail!("No public key starting with {prefix:?} found in {max_attempts} attempts")
+}
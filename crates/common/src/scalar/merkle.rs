@@ -0,0 +1,107 @@
+use super::*;
+
+// Leaves and inner nodes are hashed under different prefixes, so an inner node
+// can never be passed off as a leaf (second-preimage attack).
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+fn leaf_hash(point: &RistrettoPoint) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([LEAF_PREFIX])
+        .chain_update(compress_point(point))
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_PREFIX])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+// One level up: pairs are hashed together and an odd last node is promoted
+// unchanged (duplicating it would let `[a, b, c]` and `[a, b, c, c]` collide).
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .filter_map(|pair| {
+            pair.iter()
+                .copied()
+                .reduce(|left, right| node_hash(&left, &right))
+        })
+        .collect()
+}
+
+// Merkle root over the ring's compressed keys, in ring order. The empty ring
+// commits to the hash of nothing.
+pub fn pubring_merkle_root(ring: &PubRing) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = ring.iter().map(leaf_hash).collect();
+    loop {
+        match level.as_slice() {
+            [] => return Sha256::digest([]).into(),
+            [root] => return *root,
+            _ => level = next_level(&level),
+        }
+    }
+}
+
+// Sibling hashes from the leaf at `index` up to the root. Levels where the node
+// was promoted without a sibling contribute nothing.
+pub fn pubring_merkle_proof(ring: &PubRing, index: usize) -> AResult<Vec<[u8; 32]>> {
+    if index >= ring.len() {
+        return Err(anyhow!(
+            "Index {index} is out of range for a ring of {} keys",
+            ring.len()
+        ));
+    }
+
+    let mut proof = Vec::new();
+    let mut level: Vec<[u8; 32]> = ring.iter().map(leaf_hash).collect();
+    let mut index = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    Ok(proof)
+}
+
+// Checks that `key` sits at `index` of a ring of `ring_len` keys committed to
+// by `root`. The length is needed to know where nodes were promoted.
+pub fn verify_pubring_merkle_proof(
+    root: &[u8; 32],
+    key: &RistrettoPoint,
+    index: usize,
+    ring_len: usize,
+    proof: &[[u8; 32]],
+) -> bool {
+    if index >= ring_len {
+        return false;
+    }
+
+    let mut hash = leaf_hash(key);
+    let mut siblings = proof.iter();
+    let (mut index, mut len) = (index, ring_len);
+    while len > 1 {
+        let has_sibling = index ^ 1 < len;
+        if has_sibling {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            hash = if index % 2 == 0 {
+                node_hash(&hash, sibling)
+            } else {
+                node_hash(sibling, &hash)
+            };
+        }
+        index /= 2;
+        len = len.div_ceil(2);
+    }
+
+    siblings.next().is_none() && bool::from(hash.ct_eq(root))
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+const BLINDING_GENERATOR_DOMAIN: &[u8] = b"common_core/pedersen/blinding-generator";
+
+// The blinding generator `H`, hashed to the curve so nobody knows its discrete
+// log relative to `G` (which would let them open commitments to any value).
+pub fn pedersen_blinding_generator() -> RistrettoPoint {
+    RistrettoPoint::hash_from_bytes::<Sha512>(BLINDING_GENERATOR_DOMAIN)
+}
+
+// `value * G + blinding * H`: hiding thanks to the blinding, binding because
+// the discrete log of `H` is unknown.
+pub fn pedersen_commit(value: &Scalar, blinding: &Scalar) -> RistrettoPoint {
+    mul_base(value) + blinding * pedersen_blinding_generator()
+}
+
+// Commitments are additively homomorphic, so `commit_sub(C(v1, b1), C(v2, b2))`
+// is a commitment to `v1 - v2` opened by the blinding `b1 - b2`. Values are
+// scalars mod `l`, so a negative difference wraps; proving it did not is the
+// job of a range proof, not of this function.
+pub fn commit_sub(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+    a - b
+}
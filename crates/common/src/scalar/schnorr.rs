@@ -0,0 +1,141 @@
+use super::*;
+
+const NONCE_DOMAIN: &[u8] = b"common_core/schnorr/nonce";
+const CHALLENGE_DOMAIN: &[u8] = b"common_core/schnorr/challenge";
+
+// A Schnorr signature `(R, s)` over Ristretto, with `s * G == R + e * P` for
+// the challenge `e = H(R, P, message)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    r: CompressedRistretto,
+    s: Scalar,
+}
+
+// `R` (compressed) followed by `s`, 64 bytes in total. Decoding rejects a
+// non-canonical encoding of either half, so each signature has exactly one
+// wire form.
+impl LocalByteConvertible for Signature {
+    type Bytes = [u8; 64];
+
+    fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(self.r.as_bytes());
+        bytes[32..].copy_from_slice(self.s.as_bytes());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> AResult<Self> {
+        let bytes: &[u8; 64] = bytes
+            .try_into()
+            .map_err(|_| anyhow!("Expected 64-byte signature, got {}", bytes.len()))?;
+        let (r, s) = split_signature(bytes);
+        try_decompress(&r)?;
+        Ok(Self {
+            r: CompressedRistretto(r),
+            s: scalar_from_canonical_bytes(s)?,
+        })
+    }
+
+    fn to_base58(&self) -> String {
+        bs58::encode(self.to_bytes()).into_string()
+    }
+
+    fn from_base58_str(input: &str) -> AResult<Self> {
+        let bytes = bs58::decode(input).into_vec()?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+fn split_signature(bytes: &[u8; 64]) -> ([u8; 32], [u8; 32]) {
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&bytes[..32]);
+    s.copy_from_slice(&bytes[32..]);
+    (r, s)
+}
+
+fn challenge(r: &CompressedRistretto, public: &RistrettoPoint, message: &[u8]) -> Scalar {
+    hash_to_scalar(
+        CHALLENGE_DOMAIN,
+        &[r.as_bytes(), public.compress().as_bytes(), message],
+    )
+}
+
+// The nonce is derived from the secret and the message (as in Ed25519), so
+// signing needs no RNG and can never reuse a nonce across different messages.
+pub fn schnorr_sign(secret: &Scalar, message: &[u8]) -> Signature {
+    let nonce = hash_to_scalar(NONCE_DOMAIN, &[secret.as_bytes(), message]);
+    let r = mul_base(&nonce).compress();
+    let e = challenge(&r, &secret.compute_pubkey(), message);
+    Signature {
+        r,
+        s: nonce + e * secret,
+    }
+}
+
+pub fn schnorr_verify(public: &RistrettoPoint, message: &[u8], signature: &Signature) -> bool {
+    let Some(r) = signature.r.decompress() else {
+        return false;
+    };
+    let e = challenge(&signature.r, public, message);
+    mul_base(&signature.s) == r + e * public
+}
+
+// Why `schnorr_verify_detailed` rejected a signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum VerifyError {
+    #[error("malformed signature: {0}")]
+    MalformedSignature(&'static str),
+    // The equation `s * G == R + e * P` does not hold: the signature was made
+    // by another key, over another message, or has been tampered with.
+    #[error("signature does not match this public key and message")]
+    ChallengeMismatch,
+}
+
+// Like `schnorr_verify`, but takes the raw 64 bytes and says what is wrong, for
+// tooling that has to give actionable feedback. Prefer the boolean version in
+// hot paths.
+pub fn schnorr_verify_detailed(
+    public: &RistrettoPoint,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<(), VerifyError> {
+    let (r, s) = split_signature(signature);
+    if try_decompress(&r).is_err() {
+        return Err(VerifyError::MalformedSignature("R is not a valid point"));
+    }
+    let Ok(s) = scalar_from_canonical_bytes(s) else {
+        return Err(VerifyError::MalformedSignature(
+            "s is not a canonical scalar",
+        ));
+    };
+    let signature = Signature {
+        r: CompressedRistretto(r),
+        s,
+    };
+    if schnorr_verify(public, message, &signature) {
+        Ok(())
+    } else {
+        Err(VerifyError::ChallengeMismatch)
+    }
+}
+
+// Signs the canonical JSON of `payload` (see `to_canonical_json`), so the
+// signature survives re-serialization with a different key order.
+pub fn sign_payload<T: Serialize + ?Sized>(secret: &Scalar, payload: &T) -> AResult<Signature> {
+    Ok(schnorr_sign(secret, &to_canonical_json(payload)?))
+}
+
+// `Ok(false)` means a bad signature; `Err` only that `payload` didn't serialize.
+pub fn verify_payload<T: Serialize + ?Sized>(
+    public: &RistrettoPoint,
+    payload: &T,
+    signature: &Signature,
+) -> AResult<bool> {
+    Ok(schnorr_verify(
+        public,
+        &to_canonical_json(payload)?,
+        signature,
+    ))
+}
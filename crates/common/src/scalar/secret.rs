@@ -0,0 +1,66 @@
+use super::*;
+use zeroize::{Zeroize, Zeroizing};
+
+// The room bs58 asks for when encoding 32 bytes (its `len + (len + 1) / 2`
+// estimate, above the 44 characters actually needed). Encoding into a slice
+// this size never resizes anything, so no unwiped copy is left behind.
+const BASE58_BUF_LEN: usize = 48;
+
+// A secret scalar that is wiped when dropped. `Debug` is redacted so the key
+// never ends up in logs.
+#[derive(Clone)]
+pub struct SecretScalar(Scalar);
+
+impl SecretScalar {
+    pub fn new(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+
+    pub fn expose_secret(&self) -> &Scalar {
+        &self.0
+    }
+}
+
+impl From<Scalar> for SecretScalar {
+    fn from(scalar: Scalar) -> Self {
+        Self(scalar)
+    }
+}
+
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(..)")
+    }
+}
+
+// Base58 of the secret's canonical bytes; the byte copy, the encoding buffer
+// and the string are all wiped once dropped.
+pub fn secret_to_zeroizing_base58(secret: &SecretScalar) -> Zeroizing<String> {
+    let bytes = Zeroizing::new(secret.0.to_bytes());
+    let mut buf = Zeroizing::new([0u8; BASE58_BUF_LEN]);
+    let len = bs58::encode(bytes.as_ref())
+        .onto(buf.as_mut_slice())
+        .expect("a 32-byte input always fits BASE58_BUF_LEN");
+    // `String::from` allocates exactly `len` bytes.
+    let encoded = std::str::from_utf8(&buf[..len]).expect("base58 output is ASCII");
+    Zeroizing::new(String::from(encoded))
+}
+
+// Decodes straight into a zeroizing buffer, so no unwiped intermediate copy of
+// the secret is left behind. Input must decode to exactly 32 bytes.
+pub fn secret_from_zeroizing_base58(input: &str) -> AResult<Zeroizing<[u8; 32]>> {
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    let len = bs58::decode(input)
+        .onto(bytes.as_mut_slice())
+        .map_err(|e| anyhow!("Invalid base58 secret: {e}"))?;
+    if len != bytes.len() {
+        return Err(anyhow!("Expected 32 bytes for a secret, got {len}"));
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+// Shamir's `t`-of-`n` sharing over the scalar field: the secret is the
+// constant term of a random degree `t - 1` polynomial and share `x` is its
+// value at `x` (1..=n). Any `t` shares determine the secret; fewer reveal
+// nothing about it.
+pub fn split_secret(secret: &Scalar, t: usize, n: usize) -> AResult<Vec<(u8, Scalar)>> {
+    if t == 0 || t > n || n > 255 {
+        bail!("Shamir parameters must satisfy 1 <= t <= n <= 255, got t = {t}, n = {n}");
+    }
+    let mut coefficients = vec![*secret];
+    for _ in 1..t {
+        coefficients.push(random_scalar()?);
+    }
+    Ok((1..=n as u8)
+        .map(|x| {
+            let x_scalar = Scalar::from(x);
+            // Horner's rule, highest coefficient first.
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(Scalar::ZERO, |acc, coefficient| {
+                    acc * x_scalar + coefficient
+                });
+            (x, y)
+        })
+        .collect())
+}
+
+// Lagrange interpolation at zero. Given at least `t` shares this is the
+// secret; with fewer it is an unrelated scalar, which cannot be detected here.
+pub fn recombine(shares: &[(u8, Scalar)]) -> AResult<Scalar> {
+    if shares.is_empty() {
+        bail!("Cannot recombine zero shares");
+    }
+    for (index, (x, _)) in shares.iter().enumerate() {
+        if *x == 0 {
+            bail!("Share {index} has index 0, which is never issued");
+        }
+        if shares[..index].iter().any(|(other, _)| other == x) {
+            bail!("Duplicate share index {x}");
+        }
+    }
+    let secret = shares.iter().map(|(x_i, y_i)| {
+        let x_i = Scalar::from(*x_i);
+        let (numerator, denominator) = shares
+            .iter()
+            .map(|(x_j, _)| Scalar::from(*x_j))
+            .filter(|x_j| *x_j != x_i)
+            .fold((Scalar::ONE, Scalar::ONE), |(num, den), x_j| {
+                (num * x_j, den * (x_j - x_i))
+            });
+        y_i * numerator * denominator.invert()
+    });
+    Ok(secret.sum())
+}
@@ -0,0 +1,19 @@
+use super::*;
+
+const DETERMINISTIC_DOMAIN: &[u8] = b"common_core/test-util/deterministic-pubring";
+
+// Reproducible keys for tests: scalar `i` is a hash of `(seed, i)`, so the same
+// seed always yields the same ring, and a longer ring extends a shorter one.
+// The secrets are trivially recoverable from the seed; never use them for real.
+pub fn deterministic_pubring(seed: u64, n: usize) -> (Vec<Scalar>, PubRing) {
+    let secrets: Vec<Scalar> = (0..n as u64)
+        .map(|index| {
+            hash_to_scalar(
+                DETERMINISTIC_DOMAIN,
+                &[&seed.to_le_bytes(), &index.to_le_bytes()],
+            )
+        })
+        .collect();
+    let ring = compute_pubkeys(&secrets);
+    (secrets, ring)
+}
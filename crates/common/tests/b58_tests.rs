@@ -0,0 +1,103 @@
+use common_core::prelude::b58::*;
+use std::io::{BufReader, Read, Write};
+
+#[cfg(test)]
+mod b58_tests {
+    use super::*;
+
+    fn large_buffer() -> Vec<u8> {
+        // Leading zeros are the classic base58 edge case; put some mid-stream
+        // so a few frames start with them.
+        (0..100_000u32)
+            .map(|i| if i % 300 < 3 { 0 } else { (i * 31 % 251) as u8 })
+            .collect()
+    }
+
+    #[test]
+    fn test_base58_writer_reader_round_trip() {
+        let data = large_buffer();
+        let mut writer = Base58Writer::with_frame_len(Vec::new(), 300);
+        for piece in data.chunks(777) {
+            writer.write_all(piece).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+
+        let text = String::from_utf8(encoded.clone()).unwrap();
+        assert_eq!(text.lines().count(), data.len().div_ceil(300));
+
+        let mut decoded = Vec::new();
+        Base58Reader::new(BufReader::new(encoded.as_slice()))
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(base58_decode_framed(&text).unwrap(), data);
+    }
+
+    #[test]
+    fn test_base58_framed_one_shot_matches_writer() {
+        let data = large_buffer();
+        let mut writer = Base58Writer::new(Vec::new());
+        writer.write_all(&data).unwrap();
+        let encoded = writer.finish().unwrap();
+
+        assert_eq!(
+            base58_encode_framed(&data, DEFAULT_FRAME_LEN).as_bytes(),
+            encoded.as_slice()
+        );
+        assert_eq!(base58_encode_framed(&[], DEFAULT_FRAME_LEN), "");
+    }
+
+    #[test]
+    fn test_base58_framed_rejects_invalid_frame() {
+        assert!(base58_decode_framed("2NEpo7TZRRrLZSi2U\n0OIl\n").is_err());
+
+        let mut decoded = Vec::new();
+        let err = Base58Reader::new(BufReader::new("0OIl\n".as_bytes()))
+            .read_to_end(&mut decoded)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_base58_alphabets_differ_and_round_trip() {
+        let bytes = b"\x00\x01alphabet test";
+        let bitcoin = base58_encode_with(bytes, Bs58Alphabet::default());
+        let ripple = base58_encode_with(bytes, Bs58Alphabet::Ripple);
+
+        assert_eq!(bitcoin, bs58::encode(bytes).into_string());
+        assert_ne!(bitcoin, ripple);
+        assert_eq!(
+            base58_decode_with(&bitcoin, Bs58Alphabet::Bitcoin).unwrap(),
+            bytes
+        );
+        assert_eq!(
+            base58_decode_with(&ripple, Bs58Alphabet::Ripple).unwrap(),
+            bytes
+        );
+        assert_eq!(
+            base58_decode_with(
+                &base58_encode_with(bytes, Bs58Alphabet::Flickr),
+                Bs58Alphabet::Flickr
+            )
+            .unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_base58_custom_alphabet() {
+        let mut characters = *b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+        characters.reverse();
+        let reversed = Bs58Alphabet::custom(&characters).unwrap();
+
+        let encoded = base58_encode_with(b"custom", reversed);
+        assert_ne!(
+            encoded,
+            base58_encode_with(b"custom", Bs58Alphabet::Bitcoin)
+        );
+        assert_eq!(base58_decode_with(&encoded, reversed).unwrap(), b"custom");
+
+        characters[1] = characters[0];
+        assert!(Bs58Alphabet::custom(&characters).is_err());
+    }
+}
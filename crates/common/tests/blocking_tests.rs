@@ -0,0 +1,41 @@
+#![cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct MockData {
+    foo: String,
+    bar: u32,
+}
+
+#[test]
+fn test_get_json_blocking() {
+    // The mock server needs a runtime of its own; a multi-threaded one keeps
+    // serving while this thread blocks on the request.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mock_data = MockData {
+        foo: "sync".to_string(),
+        bar: 7,
+    };
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/blocking"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+            .mount(&server)
+            .await;
+        server
+    });
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/blocking");
+
+    let result: MockData = client.get_json_blocking(&url).unwrap();
+    assert_eq!(result, mock_data);
+
+    let result: MockData = client.get(&url).take_data_blocking().unwrap();
+    assert_eq!(result, mock_data);
+}
@@ -0,0 +1,49 @@
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod env_var_tests {
+    use super::*;
+
+    #[test]
+    fn test_env_var_set_and_unset() {
+        let name = "COMMON_CORE_TEST_ENV_VAR";
+        std::env::set_var(name, "value");
+        assert_eq!(env_var(name).unwrap(), "value");
+
+        std::env::remove_var(name);
+        let error = env_var(name).unwrap_err().to_string();
+        assert!(error.contains(name));
+    }
+
+    #[test]
+    fn test_env_parse_integer() {
+        let name = "COMMON_CORE_TEST_ENV_PARSE";
+        std::env::set_var(name, "8080");
+        assert_eq!(env_parse::<u16>(name).unwrap(), 8080);
+        std::env::remove_var(name);
+    }
+
+    #[test]
+    fn test_env_parse_failure_names_variable() {
+        let name = "COMMON_CORE_TEST_ENV_PARSE_FAILURE";
+        std::env::set_var(name, "not-a-number");
+        let error = env_parse::<u16>(name).unwrap_err().to_string();
+        assert!(error.contains(name));
+        assert!(error.contains("not-a-number"));
+        std::env::remove_var(name);
+    }
+
+    #[test]
+    fn test_env_or_falls_back_to_default() {
+        let name = "COMMON_CORE_TEST_ENV_OR";
+        std::env::remove_var(name);
+        assert_eq!(env_or(name, 3u32), 3);
+
+        std::env::set_var(name, "oops");
+        assert_eq!(env_or(name, 3u32), 3);
+
+        std::env::set_var(name, "5");
+        assert_eq!(env_or(name, 3u32), 5);
+        std::env::remove_var(name);
+    }
+}
@@ -1,10 +1,15 @@
+#![cfg(not(target_arch = "wasm32"))]
+
 use common_core::http_client::*;
 use common_core::prelude::*;
 use serde::{Deserialize, Serialize};
 use wiremock::matchers::{method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+// Tests that mutate `API_KEY`/`API_KEY_FILE` hold this lock so they don't race.
+static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[derive(Clone, Default, Deserialize, Serialize, Debug, PartialEq)]
 struct MockData {
     foo: String,
     bar: u32,
@@ -83,6 +88,7 @@ fn test_onion_client_builder_and_conversion() {
 #[test]
 #[cfg(not(target_arch = "wasm32"))]
 fn test_onion_client_from_env() {
+    let _guard = ENV_LOCK.lock().unwrap();
     // Test with env var set
     let key = "my-secret-key-from-env";
     std::env::set_var("API_KEY", key);
@@ -104,3 +110,1988 @@ fn test_onion_client_with_api_key() {
     let client_result = OnionClient::with_api_key(api_key);
     assert!(client_result.is_ok());
 }
+
+#[tokio::test]
+#[cfg(not(target_arch = "wasm32"))]
+async fn test_fetch_options_are_noops_on_native() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "cors".to_string(),
+        bar: 7,
+    };
+    Mock::given(method("GET"))
+        .and(path("/fetch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/fetch");
+
+    let result: MockData = client
+        .get(&url)
+        .with_credentials_include()
+        .with_cors_mode(CorsMode::NoCors)
+        .take_data()
+        .await
+        .unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_take_data_timeout_is_distinct_error() {
+    let server = MockServer::start().await;
+    let response = ResponseTemplate::new(200)
+        .set_body_json(MockData {
+            foo: "slow".to_string(),
+            bar: 1,
+        })
+        .set_delay(std::time::Duration::from_millis(500));
+
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(response)
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default().retry(0u32).build().unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/slow");
+
+    let result: AResult<MockData> = client
+        .get(&url)
+        .timeout(std::time::Duration::from_millis(50))
+        .take_data()
+        .await;
+
+    let error = result.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<CommonError>(),
+        Some(CommonError::Timeout)
+    ));
+}
+
+#[tokio::test]
+async fn test_request_id_header_is_generated() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "traced".to_string(),
+        bar: 36,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/request-id"))
+        .and(|req: &wiremock::Request| {
+            req.headers
+                .get("x-request-id")
+                .is_some_and(|value| value.len() == 36)
+        })
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .request_id(true)
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/request-id");
+
+    let result: MockData = client.get(&url).take_data().await.unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_request_id_header_is_not_overridden() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "mine".to_string(),
+        bar: 1,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/request-id"))
+        .and(wiremock::matchers::header("x-request-id", "caller-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .request_id(true)
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/request-id");
+
+    let result: MockData = client
+        .get(&url)
+        .header("X-Request-Id", "caller-id")
+        .take_data()
+        .await
+        .unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_req_builder_patch_with_json_body() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "patched".to_string(),
+        bar: 2,
+    };
+
+    Mock::given(method("PATCH"))
+        .and(path("/items/1"))
+        .and(wiremock::matchers::header("x-custom", "yes"))
+        .and(wiremock::matchers::body_json(&mock_data))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = Url::parse(&format!("{}{}", server.uri(), "/items/1")).unwrap();
+
+    let result: MockData = Req::new(&client)
+        .method(Method::PATCH)
+        .url(url)
+        .json(&mock_data)
+        .header("X-Custom", "yes")
+        .send_take()
+        .await
+        .unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_req_builder_delete() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "deleted".to_string(),
+        bar: 3,
+    };
+
+    Mock::given(method("DELETE"))
+        .and(path("/items/1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = Url::parse(&format!("{}{}", server.uri(), "/items/1")).unwrap();
+
+    let result: MockData = Req::new(&client)
+        .method(Method::DELETE)
+        .url(url)
+        .send_take()
+        .await
+        .unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_req_builder_reports_invalid_header() {
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = Url::parse("http://127.0.0.1:9/unused").unwrap();
+
+    let result: AResult<MockData> = Req::new(&client)
+        .url(url)
+        .header("bad header", "value")
+        .send_take()
+        .await;
+
+    assert!(result.unwrap_err().to_string().contains("bad header"));
+}
+
+#[tokio::test]
+async fn test_take_data_lenient_coerces_string_numbers() {
+    let server = MockServer::start().await;
+    let body = serde_json::json!({ "foo": "456", "bar": "123" });
+
+    Mock::given(method("GET"))
+        .and(path("/lenient"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&body))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/lenient");
+
+    let strict: AResult<MockData> = client.get(&url).take_data().await;
+    assert!(strict.is_err());
+
+    let lenient: MockData = client.get(&url).take_data_lenient().await.unwrap();
+    assert_eq!(
+        lenient,
+        MockData {
+            foo: "456".to_string(),
+            bar: 123,
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_take_data_with_custom_deserializer() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/custom"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("foo=bar"))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/custom");
+
+    let pair: (String, String) = client
+        .get(&url)
+        .take_data_with(|text| {
+            let (key, value) = text.split_once('=').ok_or_else(|| aerr!("no '='"))?;
+            Ok((key.to_string(), value.to_string()))
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(pair, ("foo".to_string(), "bar".to_string()));
+}
+
+#[tokio::test]
+async fn test_take_data_with_headers_returns_custom_header() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "paged".to_string(),
+        bar: 10,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("X-Total-Count", "42")
+                .set_body_json(&mock_data),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/items");
+
+    let (headers, result): (HeaderMap, MockData) =
+        client.get(&url).take_data_with_headers().await.unwrap();
+
+    assert_eq!(result, mock_data);
+    assert_eq!(headers.get("x-total-count").unwrap(), "42");
+}
+
+#[tokio::test]
+async fn test_onion_client_without_tracing_middleware() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "quiet".to_string(),
+        bar: 0,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/quiet"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .tracing(false)
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/quiet");
+
+    let result: MockData = client.get(&url).take_data().await.unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_body_bytes_sets_content_type_and_body() {
+    let server = MockServer::start().await;
+    let payload = vec![0x08, 0x96, 0x01];
+    let mock_data = MockData {
+        foo: "stored".to_string(),
+        bar: 150,
+    };
+
+    Mock::given(method("POST"))
+        .and(path("/upload"))
+        .and(wiremock::matchers::header(
+            "content-type",
+            "application/octet-stream",
+        ))
+        .and(wiremock::matchers::body_bytes(payload.clone()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/upload");
+
+    let result: MockData = client
+        .post(&url)
+        .body_bytes(payload, "application/octet-stream")
+        .take_data()
+        .await
+        .unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_onion_client_with_pool_settings() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "pooled".to_string(),
+        bar: 4,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/pool"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .pool_idle_timeout(Some(30_000u64))
+        .pool_max_idle_per_host(Some(4usize))
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/pool");
+
+    for _ in 0..2 {
+        let result: MockData = client.get(&url).take_data().await.unwrap();
+        assert_eq!(result, mock_data);
+    }
+}
+
+#[test]
+fn test_onion_client_http_version_toggles() {
+    let prior_knowledge: AResult<ClientWithMiddleware> = OnionClientBuilder::default()
+        .http2_prior_knowledge(true)
+        .build()
+        .unwrap()
+        .into();
+    assert!(prior_knowledge.is_ok());
+
+    let conflicting: AResult<ClientWithMiddleware> = OnionClientBuilder::default()
+        .http2_prior_knowledge(true)
+        .http1_only(true)
+        .build()
+        .unwrap()
+        .into();
+    assert!(conflicting
+        .unwrap_err()
+        .to_string()
+        .contains("mutually exclusive"));
+}
+
+#[tokio::test]
+async fn test_take_data_strict_rejects_redirect() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/moved"))
+        .respond_with(ResponseTemplate::new(302).insert_header("Location", "/login"))
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+    let url = format!("{}{}", server.uri(), "/moved");
+
+    let result: AResult<MockData> = client.get(&url).take_data_strict().await;
+
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("302"));
+    assert!(error.contains("/login"));
+}
+
+#[tokio::test]
+async fn test_from_env_blank_api_key_sends_no_auth_header() {
+    let client = {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("API_KEY", "   ");
+        let client = OnionClient::from_env();
+        std::env::remove_var("API_KEY");
+        client.unwrap()
+    };
+
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "anonymous".to_string(),
+        bar: 0,
+    };
+    Mock::given(method("GET"))
+        .and(path("/auth"))
+        .and(|req: &wiremock::Request| !req.headers.contains_key("authorization"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}{}", server.uri(), "/auth");
+    let result: MockData = client.get(&url).take_data().await.unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_from_env_or_file_reads_api_key_file() {
+    let mut key_file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(key_file, "  file-secret  ").unwrap();
+
+    let client = {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("API_KEY");
+        std::env::set_var("API_KEY_FILE", key_file.path());
+        let client = OnionClient::from_env_or_file();
+        std::env::remove_var("API_KEY_FILE");
+        client.unwrap()
+    };
+
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "authorized".to_string(),
+        bar: 1,
+    };
+    Mock::given(method("GET"))
+        .and(path("/auth"))
+        .and(wiremock::matchers::header(
+            "authorization",
+            "Bearer file-secret",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}{}", server.uri(), "/auth");
+    let result: MockData = client.get(&url).take_data().await.unwrap();
+
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_json_gzip_compresses_request_body() {
+    let server = MockServer::start().await;
+    let payload: Vec<MockData> = (0..100)
+        .map(|i| MockData {
+            foo: "repetitive payload".to_string(),
+            bar: i,
+        })
+        .collect();
+    let plain_len = serde_json::to_vec(&payload).unwrap().len();
+    let expected = serde_json::to_value(&payload).unwrap();
+
+    Mock::given(method("POST"))
+        .and(path("/bulk"))
+        .and(wiremock::matchers::header("content-encoding", "gzip"))
+        .and(move |req: &wiremock::Request| {
+            let mut decoder = flate2::read::GzDecoder::new(req.body.as_slice());
+            let mut json = String::new();
+            std::io::Read::read_to_string(&mut decoder, &mut json).is_ok()
+                && req.body.len() < plain_len
+                && serde_json::from_str::<serde_json::Value>(&json).ok() == Some(expected.clone())
+        })
+        .respond_with(ResponseTemplate::new(200).set_body_json(&payload[0]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/bulk");
+
+    let result: MockData = client
+        .post(&url)
+        .json_gzip(&payload)
+        .unwrap()
+        .take_data()
+        .await
+        .unwrap();
+
+    assert_eq!(result, payload[0]);
+}
+
+// Collects `name=value` pairs from every span created while installed.
+#[derive(Clone, Default)]
+struct SpanFieldCapture(Arc<std::sync::Mutex<Vec<String>>>);
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for SpanFieldCapture {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        _id: &tracing::span::Id,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        struct Visitor<'a>(&'a mut Vec<String>);
+        impl tracing::field::Visit for Visitor<'_> {
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+                self.0.push(format!("{}={:?}", field.name(), value));
+            }
+        }
+        attrs.record(&mut Visitor(&mut self.0.lock().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn test_correlation_id_recorded_on_span() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let capture = SpanFieldCapture::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "correlated".to_string(),
+        bar: 9,
+    };
+    Mock::given(method("GET"))
+        .and(path("/correlated"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .correlation_id(Some("order-1234".to_string()))
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}{}", server.uri(), "/correlated");
+
+    let result: MockData = client.get(&url).take_data().await.unwrap();
+
+    assert_eq!(result, mock_data);
+    let fields = capture.0.lock().unwrap();
+    assert!(fields.contains(&"correlation_id=order-1234".to_string()));
+}
+
+#[tokio::test]
+async fn test_take_data_observed_fills_observation() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "observed".to_string(),
+        bar: 5,
+    };
+    let body = serde_json::to_vec(&mock_data).unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/observed"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(body.clone(), "application/json")
+                .set_delay(std::time::Duration::from_millis(5)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/observed");
+    let mut obs = RequestObs::default();
+
+    let result: MockData = client.get(&url).take_data_observed(&mut obs).await.unwrap();
+
+    assert_eq!(result, mock_data);
+    assert_eq!(obs.status(), Some(reqwest::StatusCode::OK));
+    assert_eq!(obs.body_bytes(), body.len());
+    assert!(obs.elapsed() > std::time::Duration::ZERO);
+}
+
+#[tokio::test]
+async fn test_take_data_observed_reports_status_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/observed"))
+        .respond_with(ResponseTemplate::new(503).set_body_string("down"))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/observed");
+    let mut obs = RequestObs::default();
+
+    let Err(err) = client
+        .get(&url)
+        .take_data_observed::<MockData>(&mut obs)
+        .await
+    else {
+        panic!("expected the 503 to fail");
+    };
+
+    assert!(matches!(
+        err.downcast_ref::<CommonError>(),
+        Some(CommonError::Status { status, body, .. }) if status.as_u16() == 503 && body == "down"
+    ));
+    assert_eq!(obs.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(obs.body_bytes(), 4);
+}
+
+#[tokio::test]
+async fn test_take_data_if_modified_since() {
+    let server = MockServer::start().await;
+    let last_modified = "Wed, 21 Oct 2015 07:28:00 GMT";
+    let mock_data = MockData {
+        foo: "cached".to_string(),
+        bar: 304,
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        // `header` splits values on commas, which HTTP dates contain.
+        .and(move |req: &wiremock::Request| {
+            req.headers
+                .get("if-modified-since")
+                .map(|v| v == last_modified)
+                .unwrap_or(false)
+        })
+        .respond_with(ResponseTemplate::new(304))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/resource"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Last-Modified", last_modified)
+                .set_body_json(&mock_data),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/resource");
+
+    let fresh: Conditional<MockData> = client
+        .get(&url)
+        .take_data_if_modified_since(None)
+        .await
+        .unwrap();
+    let Conditional::Fresh(result, Some(date)) = fresh else {
+        panic!("expected a fresh response with Last-Modified, got {fresh:?}");
+    };
+    assert_eq!(result, mock_data);
+    assert_eq!(date.to_string(), last_modified);
+
+    let revalidated: Conditional<MockData> = client
+        .get(&url)
+        .take_data_if_modified_since(Some(date))
+        .await
+        .unwrap();
+    assert_eq!(revalidated, Conditional::NotModified);
+
+    // An older date doesn't match the 304 mock, so the server sends the body.
+    let stale: HttpDate = "Tue, 20 Oct 2015 07:28:00 GMT".parse().unwrap();
+    let refetched: Conditional<MockData> = client
+        .get(&url)
+        .take_data_if_modified_since(Some(stale))
+        .await
+        .unwrap();
+    assert!(matches!(refetched, Conditional::Fresh(result, _) if result == mock_data));
+}
+
+#[test]
+fn test_headers_from_pairs() {
+    let headers = headers_from_pairs([
+        ("Accept".to_string(), "application/json".to_string()),
+        ("x-tenant".to_string(), "acme".to_string()),
+    ])
+    .unwrap();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers["accept"], "application/json");
+    assert_eq!(headers["x-tenant"], "acme");
+
+    let err = headers_from_pairs([("bad header".to_string(), "v".to_string())]).unwrap_err();
+    assert!(err.to_string().contains("bad header"), "{err}");
+
+    let err = headers_from_pairs([("x-ok".to_string(), "line\nbreak".to_string())]).unwrap_err();
+    assert!(err.to_string().contains("x-ok"), "{err}");
+}
+
+#[test]
+fn test_api_key_with_invalid_header_characters() {
+    let err = OnionClient::with_api_key("abc\ndef".to_string()).unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("API key contains invalid header characters"),
+        "{message}"
+    );
+    assert!(!message.contains("abc"), "the key must not leak: {message}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_single_flight_coalesces_concurrent_gets() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "shared".to_string(),
+        bar: 10,
+    };
+    // The delay keeps the first request in flight while the others join it.
+    Mock::given(method("GET"))
+        .and(path("/shared"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(&mock_data)
+                .set_delay(std::time::Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let flights = SingleFlight::<MockData>::new(&client);
+    let url = format!("{}{}", server.uri(), "/shared");
+
+    let tasks = (0..10).map(|_| {
+        let flights = flights.clone();
+        let url = url.clone();
+        tokio::spawn(async move { flights.get(&url).await })
+    });
+    let results = futures::future::join_all(tasks).await;
+
+    assert_eq!(results.len(), 10);
+    for result in results {
+        assert_eq!(result.unwrap().unwrap(), mock_data);
+    }
+    server.verify().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_single_flight_shares_error_with_every_awaiter() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/missing"))
+        .respond_with(
+            ResponseTemplate::new(404)
+                .set_body_string("not found")
+                .set_delay(std::time::Duration::from_millis(200)),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let flights = SingleFlight::<MockData>::new(&client);
+    let url = format!("{}{}", server.uri(), "/missing");
+
+    let tasks = (0..10).map(|_| {
+        let flights = flights.clone();
+        let url = url.clone();
+        tokio::spawn(async move { flights.get(&url).await })
+    });
+    let results = futures::future::join_all(tasks).await;
+
+    for result in results {
+        let Err(err) = result.unwrap() else {
+            panic!("expected the 404 to fail every awaiter");
+        };
+        assert!(matches!(
+            err.downcast_ref::<CommonError>(),
+            Some(CommonError::Status { status, .. }) if status.as_u16() == 404
+        ));
+    }
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_take_data_rejects_non_json_content_type() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/html"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            "<html><body>Maintenance</body></html>",
+            "text/html; charset=utf-8",
+        ))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/html");
+
+    let err = client.get(&url).take_data::<MockData>().await.unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("Expected JSON, got text/html"),
+        "{message}"
+    );
+    assert!(message.contains("Maintenance"), "{message}");
+}
+
+#[tokio::test]
+async fn test_paginate_follows_link_next() {
+    use futures::StreamExt;
+
+    let server = MockServer::start().await;
+    let first = vec![MockData {
+        foo: "page".to_string(),
+        bar: 1,
+    }];
+    let second = vec![MockData {
+        foo: "page".to_string(),
+        bar: 2,
+    }];
+
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .and(wiremock::matchers::query_param("page", "2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Link", r#"</items?page=1>; rel="prev""#)
+                .set_body_json(&second),
+        )
+        .with_priority(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header(
+                    "Link",
+                    format!(
+                        r#"<{}/items?page=2>; rel="next", </items?page=9>; rel="last""#,
+                        server.uri()
+                    ),
+                )
+                .set_body_json(&first),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let start = Url::parse(&format!("{}/items", server.uri())).unwrap();
+
+    let pages: Vec<Vec<MockData>> = paginate(start, |url| client.get(url.clone()))
+        .map(|page| page.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(pages, vec![first, second]);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_base_url_client_joins_relative_paths() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "relative".to_string(),
+        bar: 5,
+    };
+    Mock::given(method("GET"))
+        .and(path("/api/v1/items"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let base = Url::parse(&format!("{}/api/v1", server.uri())).unwrap();
+    let client: AResult<BaseUrlClient> = OnionClientBuilder::default()
+        .retry(0u32)
+        .base_url(base)
+        .build()
+        .unwrap()
+        .into();
+    let client = client.unwrap();
+
+    for path in ["items", "/items"] {
+        let result: MockData = client.get_rel(path).unwrap().take_data().await.unwrap();
+        assert_eq!(result, mock_data);
+    }
+    assert_eq!(
+        client.url_for("items?page=2").unwrap().as_str(),
+        format!("{}/api/v1/items?page=2", server.uri())
+    );
+    server.verify().await;
+}
+
+#[test]
+fn test_base_url_client_without_base_errors() {
+    let client: AResult<BaseUrlClient> = OnionClientBuilder::default().build().unwrap().into();
+    let err = client.unwrap().get_rel("items").unwrap_err();
+    assert!(err.to_string().contains("No base_url is set"), "{err}");
+}
+
+#[test]
+fn test_plain_client_rejects_base_url() {
+    let client: AResult<ClientWithMiddleware> = OnionClientBuilder::default()
+        .base_url(Url::parse("https://example.com/api/").unwrap())
+        .build()
+        .unwrap()
+        .into();
+    let Err(err) = client else {
+        panic!("expected the base_url to be rejected");
+    };
+    assert!(
+        err.to_string().contains("only used by BaseUrlClient"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_take_sse_parses_events() {
+    use futures::StreamExt;
+
+    let server = MockServer::start().await;
+    let body = ": keep-alive comment\n\
+                event: update\n\
+                id: 1\n\
+                data: first line\n\
+                data: second line\n\
+                \n\
+                data: {\"plain\":true}\r\n\
+                \r\n";
+    Mock::given(method("GET"))
+        .and(path("/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/events");
+
+    let events: Vec<SseEvent> = client
+        .get(&url)
+        .take_sse()
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(
+        events,
+        vec![
+            SseEvent {
+                event: Some("update".to_string()),
+                data: "first line\nsecond line".to_string(),
+                id: Some("1".to_string()),
+            },
+            SseEvent {
+                event: None,
+                data: r#"{"plain":true}"#.to_string(),
+                id: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_full_jitter_delay_bounds() {
+    for attempt in 0..8 {
+        let ceiling = 1_000u64.min(10 * 2u64.pow(attempt));
+        for _ in 0..50 {
+            let delay = full_jitter_delay(10, 1_000, attempt);
+            assert!(
+                delay <= std::time::Duration::from_millis(ceiling),
+                "{delay:?}"
+            );
+        }
+    }
+    // The cap wins once the exponential term overtakes it, even on overflow.
+    assert!(full_jitter_delay(u64::MAX, 5, 100) <= std::time::Duration::from_millis(5));
+}
+
+#[tokio::test]
+async fn test_full_jitter_retries_flaky_endpoint() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "eventually".to_string(),
+        bar: 3,
+    };
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+
+    let client: AResult<ClientWithMiddleware> = OnionClientBuilder::default()
+        .retry(3u32)
+        .backoff(BackoffKind::FullJitter { base: 10, cap: 50 })
+        .build()
+        .unwrap()
+        .into();
+    let url = format!("{}{}", server.uri(), "/flaky");
+
+    let result: MockData = client.unwrap().get(&url).take_data().await.unwrap();
+    assert_eq!(result, mock_data);
+    assert_eq!(server.received_requests().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_take_enveloped() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "wrapped".to_string(),
+        bar: 8,
+    };
+    Mock::given(method("GET"))
+        .and(path("/wrapped"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "data": mock_data,
+            "meta": { "page": 1 },
+        })))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/wrapped");
+
+    let result: MockData = client.get(&url).take_enveloped("data").await.unwrap();
+    assert_eq!(result, mock_data);
+
+    let err = client
+        .get(&url)
+        .take_enveloped::<MockData>("payload")
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains(r#"no "payload" field"#), "{err}");
+}
+
+#[tokio::test]
+async fn test_take_data_or_default() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "present".to_string(),
+        bar: 9,
+    };
+    Mock::given(method("POST"))
+        .and(path("/fire"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/echo"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/html"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw("<html>Maintenance</html>", "text/html"),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+
+    let empty: MockData = client
+        .post(format!("{}/fire", server.uri()))
+        .take_data_or_default()
+        .await
+        .unwrap();
+    assert_eq!(empty, MockData::default());
+
+    let present: MockData = client
+        .post(format!("{}/echo", server.uri()))
+        .take_data_or_default()
+        .await
+        .unwrap();
+    assert_eq!(present, mock_data);
+
+    let err = client
+        .post(format!("{}/html", server.uri()))
+        .take_data_or_default::<MockData>()
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("Expected JSON, got text/html"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_take_data_debug_quotes_malformed_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/malformed"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"{"foo": "x", "bar": oops}"#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/malformed");
+
+    let err = client
+        .get(&url)
+        .take_data_debug::<MockData>()
+        .await
+        .unwrap_err();
+    let message = format!("{err:#}");
+    assert!(message.contains(r#"\"bar\": oops"#), "{message}");
+}
+
+#[tokio::test]
+async fn test_take_data_decompress() {
+    use flate2::{
+        write::{GzEncoder, ZlibEncoder},
+        Compression,
+    };
+    use std::io::Write;
+
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "compressed".to_string(),
+        bar: 162,
+    };
+    let json = serde_json::to_vec(&mock_data).unwrap();
+
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(&json).unwrap();
+    let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+    zlib.write_all(&json).unwrap();
+
+    for (route, encoding, body) in [
+        ("/gzip", "gzip", gzip.finish().unwrap()),
+        ("/deflate", "deflate", zlib.finish().unwrap()),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", encoding)
+                    .set_body_raw(body, "application/json"),
+            )
+            .mount(&server)
+            .await;
+    }
+
+    // The client has no automatic decompression, so the body arrives as-is.
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    for route in ["/gzip", "/deflate"] {
+        let url = format!("{}{}", server.uri(), route);
+        assert!(client.get(&url).take_data::<MockData>().await.is_err());
+        let result: MockData = client.get(&url).take_data_decompress().await.unwrap();
+        assert_eq!(result, mock_data);
+    }
+
+    // A compressed HTML page fails the same way an uncompressed one does.
+    let mut html = GzEncoder::new(Vec::new(), Compression::default());
+    html.write_all(b"<html>Maintenance</html>").unwrap();
+    Mock::given(method("GET"))
+        .and(path("/html"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_raw(html.finish().unwrap(), "text/html"),
+        )
+        .mount(&server)
+        .await;
+    let err = client
+        .get(format!("{}/html", server.uri()))
+        .take_data_decompress::<MockData>()
+        .await
+        .unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains("Expected JSON, got text/html"),
+        "{message}"
+    );
+    assert!(message.contains("Maintenance"), "{message}");
+}
+
+#[tokio::test]
+async fn test_take_verified() {
+    use sha2::{Digest, Sha256};
+
+    let server = MockServer::start().await;
+    let payload = vec![0xabu8; 64 * 1024];
+    Mock::given(method("GET"))
+        .and(path("/download"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(payload.clone()))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/download");
+
+    let expected: [u8; 32] = Sha256::digest(&payload).into();
+    let body = client.get(&url).take_verified(expected).await.unwrap();
+    assert_eq!(body, payload);
+
+    let err = client.get(&url).take_verified([0u8; 32]).await.unwrap_err();
+    assert!(err.to_string().contains("SHA-256 mismatch"), "{err}");
+}
+
+#[tokio::test]
+async fn test_download_resumable_appends_partial_content() {
+    let server = MockServer::start().await;
+    let full: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+    let offset = 4_000usize;
+
+    Mock::given(method("GET"))
+        .and(path("/artifact"))
+        .and(wiremock::matchers::header("range", "bytes=4000-"))
+        .respond_with(
+            ResponseTemplate::new(206)
+                .insert_header("Content-Range", format!("bytes 4000-9999/{}", full.len()))
+                .set_body_bytes(full[offset..].to_vec()),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("artifact.bin");
+    std::fs::write(&file, &full[..offset]).unwrap();
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/artifact");
+
+    let size = client
+        .get(&url)
+        .download_resumable(&file, Some(full.len() as u64))
+        .await
+        .unwrap();
+    assert_eq!(size, full.len() as u64);
+    assert_eq!(std::fs::read(&file).unwrap(), full);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn test_download_resumable_restarts_when_range_ignored() {
+    let server = MockServer::start().await;
+    let full = b"the whole file, served from the start".to_vec();
+    Mock::given(method("GET"))
+        .and(path("/artifact"))
+        .respond_with(ResponseTemplate::new(200).set_body_bytes(full.clone()))
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("artifact.bin");
+    std::fs::write(&file, b"stale partial").unwrap();
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}{}", server.uri(), "/artifact");
+
+    let size = client
+        .get(&url)
+        .download_resumable(&file, None)
+        .await
+        .unwrap();
+    assert_eq!(size, full.len() as u64);
+    assert_eq!(std::fs::read(&file).unwrap(), full);
+}
+
+#[tokio::test]
+async fn test_local_address_binds_outbound_requests() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "bound".to_string(),
+        bar: 127,
+    };
+    Mock::given(method("GET"))
+        .and(path("/bound"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+        .mount(&server)
+        .await;
+
+    let client: AResult<ClientWithMiddleware> = OnionClientBuilder::default()
+        .retry(0u32)
+        .local_address(std::net::IpAddr::from([127, 0, 0, 1]))
+        .build()
+        .unwrap()
+        .into();
+    let url = format!("{}{}", server.uri(), "/bound");
+
+    let result: MockData = client.unwrap().get(&url).take_data().await.unwrap();
+    assert_eq!(result, mock_data);
+}
+
+#[tokio::test]
+async fn test_error_status_keeps_response_headers() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/protected"))
+        .respond_with(
+            ResponseTemplate::new(401)
+                .insert_header("WWW-Authenticate", "Bearer realm=\"api\"")
+                .set_body_string("unauthorized"),
+        )
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default().retry(0u32).build().unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}/protected", server.uri());
+
+    let error = client.get(&url).take_data::<MockData>().await.unwrap_err();
+
+    let Some(CommonError::Status {
+        status,
+        headers,
+        body,
+    }) = error.downcast_ref::<CommonError>()
+    else {
+        panic!("expected CommonError::Status, got {error:?}");
+    };
+    assert_eq!(*status, reqwest::StatusCode::UNAUTHORIZED);
+    assert_eq!(headers["www-authenticate"], "Bearer realm=\"api\"");
+    assert_eq!(body, "unauthorized");
+}
+
+#[tokio::test]
+async fn test_take_json_array_stream_yields_elements() {
+    use futures::StreamExt;
+
+    let server = MockServer::start().await;
+    let body = r#" [ {"foo":"a, [b]","bar":1}, {"foo":"q\"uote","bar":"oops"},
+        {"foo":"c","bar":3} ] "#;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}/items", server.uri());
+
+    let items: Vec<AResult<MockData>> = client
+        .get(&url)
+        .take_json_array_stream()
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 3);
+    assert_eq!(
+        items[0].as_ref().unwrap(),
+        &MockData {
+            foo: "a, [b]".to_string(),
+            bar: 1
+        }
+    );
+    assert!(items[1].is_err());
+    assert_eq!(items[2].as_ref().unwrap().foo, "c");
+}
+
+#[tokio::test]
+async fn test_take_json_array_stream_reports_truncated_body() {
+    use futures::StreamExt;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/items"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(r#"[{"foo":"a","bar":1},{"foo""#, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}/items", server.uri());
+
+    let items: Vec<AResult<MockData>> = client
+        .get(&url)
+        .take_json_array_stream()
+        .await
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(items.len(), 2);
+    assert!(items[0].is_ok());
+    assert!(items[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("closing"));
+}
+
+#[tokio::test]
+async fn test_health_check_ok_and_unavailable() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/healthz"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/down"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default().retry(0u32).build().unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+
+    let healthy = Url::parse(&format!("{}/healthz", server.uri())).unwrap();
+    health_check(&client, &healthy).await.unwrap();
+
+    let down = Url::parse(&format!("{}/down", server.uri())).unwrap();
+    let message = health_check(&client, &down).await.unwrap_err().to_string();
+    assert!(message.contains("503 Service Unavailable"), "{message}");
+    assert!(message.contains("/down"), "{message}");
+}
+
+#[tokio::test]
+async fn test_take_jsonrpc_result_and_error() {
+    let server = MockServer::start().await;
+    let mock_data = MockData {
+        foo: "rpc".to_string(),
+        bar: 2,
+    };
+    Mock::given(method("POST"))
+        .and(path("/ok"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "result": mock_data,
+            "id": 1,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/fail"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "error": { "code": -32601, "message": "Method not found", "data": "nope" },
+            "id": 1,
+        })))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+
+    let result: MockData = client
+        .post(format!("{}/ok", server.uri()))
+        .take_jsonrpc()
+        .await
+        .unwrap();
+    assert_eq!(result, mock_data);
+
+    let err = client
+        .post(format!("{}/fail", server.uri()))
+        .take_jsonrpc::<MockData>()
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "JSON-RPC error -32601: Method not found");
+    let Some(CommonError::JsonRpc { code, data, .. }) = err.downcast_ref::<CommonError>() else {
+        panic!("expected CommonError::JsonRpc, got {err:?}");
+    };
+    assert_eq!(*code, -32601);
+    assert_eq!(data.as_ref(), Some(&serde_json::json!("nope")));
+}
+
+#[tokio::test]
+async fn test_take_data_deadline_cuts_retries_short() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503).set_delay(std::time::Duration::from_millis(50)))
+        .mount(&server)
+        .await;
+
+    // Retries back off for the 60s client timeout, far beyond the deadline.
+    let onion_client = OnionClientBuilder::default().retry(3u32).build().unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let url = format!("{}/flaky", server.uri());
+    let deadline = std::time::Duration::from_millis(300);
+
+    let started = std::time::Instant::now();
+    let error = client
+        .get(&url)
+        .take_data_deadline::<MockData>(deadline)
+        .await
+        .unwrap_err();
+
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    assert!(matches!(
+        error.downcast_ref::<CommonError>(),
+        Some(CommonError::DeadlineExceeded(d)) if *d == deadline
+    ));
+}
+
+#[tokio::test]
+async fn test_take_map_converts_dto() {
+    #[derive(Debug, PartialEq)]
+    struct Domain {
+        label: String,
+        count: u64,
+    }
+
+    fn to_domain(dto: MockData) -> AResult<Domain> {
+        if dto.bar == 0 {
+            bail!("count must be positive");
+        }
+        Ok(Domain {
+            label: dto.foo.to_uppercase(),
+            count: u64::from(dto.bar),
+        })
+    }
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/dto"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData {
+            foo: "widget".to_string(),
+            bar: 4,
+        }))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/empty"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData::default()))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+
+    let domain = client
+        .get(format!("{}/dto", server.uri()))
+        .take_map(to_domain)
+        .await
+        .unwrap();
+    assert_eq!(
+        domain,
+        Domain {
+            label: "WIDGET".to_string(),
+            count: 4
+        }
+    );
+
+    let err = client
+        .get(format!("{}/empty", server.uri()))
+        .take_map(to_domain)
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "count must be positive");
+}
+
+#[tokio::test]
+async fn test_shared_client_is_reused() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/shared"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData::default()))
+        .mount(&server)
+        .await;
+
+    let (first, second) = {
+        let _guard = ENV_LOCK.lock().unwrap();
+        (shared_client().unwrap(), shared_client().unwrap())
+    };
+    assert!(std::ptr::eq(first, second));
+
+    let data: MockData = first
+        .get(format!("{}/shared", server.uri()))
+        .take_data()
+        .await
+        .unwrap();
+    assert_eq!(data, MockData::default());
+}
+
+#[tokio::test]
+async fn test_resolve_overrides_dns() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/resolved"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData::default()))
+        .mount(&server)
+        .await;
+
+    let addr = *server.address();
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .resolve(vec![("upstream.internal.test".to_string(), addr)])
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+
+    let url = format!("http://upstream.internal.test:{}/resolved", addr.port());
+    let data: MockData = client.get(&url).take_data().await.unwrap();
+    assert_eq!(data, MockData::default());
+}
+
+#[tokio::test]
+async fn test_take_data_retry_if_polls_until_ready() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/job"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData {
+            foo: "pending".to_string(),
+            bar: 0,
+        }))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/job"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData {
+            foo: "ready".to_string(),
+            bar: 1,
+        }))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let url = format!("{}/job", server.uri());
+
+    let delay = std::time::Duration::from_millis(100);
+
+    let started = std::time::Instant::now();
+    let data: MockData = take_data_retry_if(
+        || client.get(&url),
+        3,
+        delay,
+        |data: &MockData| data.foo == "pending",
+    )
+    .await
+    .unwrap();
+    assert_eq!(data.foo, "ready");
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    // One pending answer, so exactly one pause before the second attempt.
+    assert!(started.elapsed() >= delay, "{:?}", started.elapsed());
+
+    let started = std::time::Instant::now();
+    let err = take_data_retry_if(|| client.get(&url), 3, delay, |_: &MockData| true)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("after 3 attempts"), "{err}");
+    // No pause after the final attempt.
+    let elapsed = started.elapsed();
+    assert!(elapsed >= delay * 2 && elapsed < delay * 3, "{elapsed:?}");
+}
+
+#[test]
+fn test_build_validated_rejects_nonsense() {
+    let cases: [(OnionClientBuilder, &str); 4] = [
+        (
+            OnionClientBuilder::default().timeout(0u64).clone(),
+            "timeout must be greater than 0",
+        ),
+        (
+            OnionClientBuilder::default()
+                .timeout(5_000u64)
+                .max_retry_interval(1_000u64)
+                .clone(),
+            "max_retry_interval (1000 ms) must be at least timeout (5000 ms)",
+        ),
+        (
+            OnionClientBuilder::default().retry(u32::MAX).clone(),
+            "retry must be at most 10",
+        ),
+        (
+            OnionClientBuilder::default()
+                .http2_prior_knowledge(true)
+                .http1_only(true)
+                .clone(),
+            "mutually exclusive",
+        ),
+    ];
+    for (builder, expected) in cases {
+        let Err(err) = builder.build_validated() else {
+            panic!("expected {expected:?} to be rejected");
+        };
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+
+    let config = OnionClientBuilder::default()
+        .retry(2u32)
+        .timeout(1_000u64)
+        .build_validated()
+        .unwrap();
+    assert!(AResult::<ClientWithMiddleware>::from(config).is_ok());
+}
+
+#[tokio::test]
+async fn test_default_query_is_appended() {
+    use wiremock::matchers::query_param;
+
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(query_param("api_version", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData::default()))
+        .mount(&server)
+        .await;
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .default_query(vec![("api_version".to_string(), "2".to_string())])
+        .build()
+        .unwrap();
+    let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+
+    let _: MockData = client
+        .get(format!("{}/a", server.uri()))
+        .take_data()
+        .await
+        .unwrap();
+    let _: MockData = client
+        .get(format!("{}/b?page=3", server.uri()))
+        .take_data()
+        .await
+        .unwrap();
+    // A caller-supplied value is kept rather than duplicated or replaced.
+    let overridden = client
+        .get(format!("{}/c?api_version=1", server.uri()))
+        .take_data::<MockData>()
+        .await;
+    assert!(overridden.is_err());
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests[0].url.query(), Some("api_version=2"));
+    assert_eq!(requests[1].url.query(), Some("page=3&api_version=2"));
+    assert_eq!(requests[2].url.query(), Some("api_version=1"));
+}
+
+#[test]
+fn test_merge_headers_policies() {
+    let pairs = |items: &[(&str, &str)]| {
+        headers_from_pairs(
+            items
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        )
+        .unwrap()
+    };
+    let values = |headers: &HeaderMap, name: &str| -> Vec<String> {
+        headers
+            .get_all(name)
+            .iter()
+            .map(|value| value.to_str().unwrap().to_string())
+            .collect()
+    };
+    let base = pairs(&[("accept", "text/plain"), ("x-base", "1")]);
+    let overlay = pairs(&[
+        ("accept", "application/json"),
+        ("accept", "application/cbor"),
+        ("x-overlay", "2"),
+    ]);
+
+    let merged = merge_headers(base.clone(), overlay.clone(), MergePolicy::OverlayWins);
+    assert_eq!(
+        values(&merged, "accept"),
+        ["application/json", "application/cbor"]
+    );
+
+    let merged = merge_headers(base.clone(), overlay.clone(), MergePolicy::BaseWins);
+    assert_eq!(values(&merged, "accept"), ["text/plain"]);
+
+    let merged = merge_headers(base, overlay, MergePolicy::Append);
+    assert_eq!(
+        values(&merged, "accept"),
+        ["text/plain", "application/json", "application/cbor"]
+    );
+    // Non-conflicting names survive under every policy.
+    assert_eq!(values(&merged, "x-base"), ["1"]);
+    assert_eq!(values(&merged, "x-overlay"), ["2"]);
+}
+
+#[tokio::test]
+async fn test_take_data_lenient_text_bom_and_charset() {
+    let server = MockServer::start().await;
+    let mut bom_body = b"\xEF\xBB\xBF".to_vec();
+    bom_body.extend_from_slice(br#"{"foo":"bom","bar":1}"#);
+    Mock::given(method("GET"))
+        .and(path("/bom"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(bom_body, "application/json"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/latin1"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(
+            b"{\"foo\":\"caf\xE9\",\"bar\":2}".to_vec(),
+            "application/json; charset=\"ISO-8859-1\"",
+        ))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+    let bom_url = format!("{}/bom", server.uri());
+
+    assert!(client.get(&bom_url).take_data::<MockData>().await.is_err());
+    let data: MockData = client.get(&bom_url).take_data_lenient_text().await.unwrap();
+    assert_eq!(data.foo, "bom");
+
+    let data: MockData = client
+        .get(format!("{}/latin1", server.uri()))
+        .take_data_lenient_text()
+        .await
+        .unwrap();
+    assert_eq!(data.foo, "café");
+}
+
+#[tokio::test]
+async fn test_take_data_with_redirects_records_hops() {
+    let server = MockServer::start().await;
+    let redirect = |to: &str| {
+        ResponseTemplate::new(302).insert_header("Location", format!("{}{to}", server.uri()))
+    };
+    Mock::given(method("GET"))
+        .and(path("/start"))
+        .respond_with(redirect("/hop"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/hop"))
+        .respond_with(redirect("/final"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/final"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(MockData {
+            foo: "landed".to_string(),
+            bar: 2,
+        }))
+        .mount(&server)
+        .await;
+
+    let client = OnionClient::with_api_key("key".to_string()).unwrap();
+
+    let (chain, data): (Vec<Url>, MockData) = client
+        .get(format!("{}/start", server.uri()))
+        .take_data_with_redirects()
+        .await
+        .unwrap();
+    let paths: Vec<&str> = chain.iter().map(Url::path).collect();
+    assert_eq!(paths, ["/hop", "/final"]);
+    assert_eq!(data.foo, "landed");
+
+    let (chain, _): (Vec<Url>, MockData) = client
+        .get(format!("{}/final", server.uri()))
+        .take_data_with_redirects()
+        .await
+        .unwrap();
+    assert!(chain.is_empty());
+
+    let onion_client = OnionClientBuilder::default()
+        .retry(0u32)
+        .max_redirects(1usize)
+        .build()
+        .unwrap();
+    let capped: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+    let err = capped
+        .get(format!("{}/start", server.uri()))
+        .take_data_with_redirects::<MockData>()
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Stopped after 1 redirects"),
+        "{err:#}"
+    );
+}
+
+#[tokio::test]
+async fn test_is_retryable_wasm_classifier() {
+    let server = MockServer::start().await;
+    for (route, status) in [("/bad", 400), ("/busy", 503), ("/limited", 429)] {
+        Mock::given(method("GET"))
+            .and(path(route))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/slow"))
+        .respond_with(ResponseTemplate::new(200).set_delay(std::time::Duration::from_secs(2)))
+        .mount(&server)
+        .await;
+
+    let client = Client::new();
+    let status_error = |route: &str| {
+        let request = client.get(format!("{}{route}", server.uri()));
+        async move {
+            request
+                .send()
+                .await
+                .unwrap()
+                .error_for_status()
+                .unwrap_err()
+        }
+    };
+
+    assert!(!is_retryable_wasm(&status_error("/bad").await));
+    assert!(is_retryable_wasm(&status_error("/busy").await));
+    assert!(is_retryable_wasm(&status_error("/limited").await));
+
+    let timeout = client
+        .get(format!("{}/slow", server.uri()))
+        .timeout(std::time::Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap_err();
+    assert!(timeout.is_timeout());
+    assert!(is_retryable_wasm(&timeout));
+}
+
+#[tokio::test]
+async fn test_send_with_retry_retries_transient_only() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/flaky"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/bad"))
+        .respond_with(ResponseTemplate::new(400))
+        .mount(&server)
+        .await;
+
+    let client = Client::new();
+    let delay = std::time::Duration::from_millis(10);
+
+    let response = send_with_retry(|| client.get(format!("{}/flaky", server.uri())), 3, delay)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    assert!(
+        send_with_retry(|| client.get(format!("{}/bad", server.uri())), 3, delay)
+            .await
+            .is_err()
+    );
+    let paths: Vec<String> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.url.path().to_string())
+        .collect();
+    assert_eq!(paths, ["/flaky", "/flaky", "/bad"]);
+}
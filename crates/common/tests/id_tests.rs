@@ -0,0 +1,24 @@
+#![cfg(feature = "uuid")]
+
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod id_tests {
+    use super::*;
+
+    #[test]
+    fn test_uuid_base58_roundtrip() {
+        let uuid = new_uuid();
+        let encoded = uuid_to_base58(&uuid);
+
+        assert!(encoded.len() <= 22);
+        assert_eq!(uuid_from_base58(&encoded).unwrap(), uuid);
+    }
+
+    #[test]
+    fn test_uuid_from_base58_rejects_wrong_length() {
+        let encoded = bs58::encode([1u8; 15]).into_string();
+        let error = uuid_from_base58(&encoded).unwrap_err().to_string();
+        assert!(error.contains("got 15"));
+    }
+}
@@ -0,0 +1,87 @@
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod json_tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Forward {
+        alpha: u32,
+        beta: String,
+        nested: Vec<ForwardInner>,
+    }
+
+    #[derive(Serialize)]
+    struct ForwardInner {
+        x: bool,
+        y: Option<u8>,
+    }
+
+    #[derive(Serialize)]
+    struct Backward {
+        nested: Vec<BackwardInner>,
+        beta: String,
+        alpha: u32,
+    }
+
+    #[derive(Serialize)]
+    struct BackwardInner {
+        y: Option<u8>,
+        x: bool,
+    }
+
+    #[test]
+    fn test_canonical_json_ignores_field_declaration_order() {
+        let forward = Forward {
+            alpha: 1,
+            beta: "two".to_string(),
+            nested: vec![ForwardInner { x: true, y: None }],
+        };
+        let backward = Backward {
+            nested: vec![BackwardInner { y: None, x: true }],
+            beta: "two".to_string(),
+            alpha: 1,
+        };
+
+        let canonical = to_canonical_json(&forward).unwrap();
+        assert_eq!(canonical, to_canonical_json(&backward).unwrap());
+        assert_eq!(
+            canonical,
+            br#"{"alpha":1,"beta":"two","nested":[{"x":true,"y":null}]}"#
+        );
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Payload {
+        id: u32,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn test_json_helpers_round_trip() {
+        let payload = Payload {
+            id: 7,
+            tags: vec!["a".to_string(), "b".to_string()],
+        };
+
+        let compact = to_json(&payload).unwrap();
+        assert_eq!(compact, r#"{"id":7,"tags":["a","b"]}"#);
+        assert_eq!(from_json::<Payload>(&compact).unwrap(), payload);
+
+        let pretty = to_json_pretty(&payload).unwrap();
+        assert!(pretty.contains('\n'));
+        assert_eq!(from_json::<Payload>(&pretty).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_from_json_error_has_context() {
+        let input = r#"{"id":"seven","tags":[]}"#;
+        let err = from_json::<Payload>(input).unwrap_err();
+        let message = format!("{err:#}");
+
+        assert!(message.contains("Payload"), "{message}");
+        assert!(message.contains("seven"), "{message}");
+        // The underlying serde error is kept as the cause.
+        assert!(message.contains("line 1"), "{message}");
+    }
+}
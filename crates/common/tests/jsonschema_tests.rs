@@ -0,0 +1,76 @@
+#![cfg(all(feature = "jsonschema", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(test)]
+mod jsonschema_tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u32,
+        name: String,
+    }
+
+    fn item_schema() -> jsonschema::Validator {
+        jsonschema::validator_for(&serde_json::json!({
+            "type": "object",
+            "required": ["id", "name"],
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "name": { "type": "string", "minLength": 1 }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_take_data_validated() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/good"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 7, "name": "bolt" })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/bad"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 0, "name": "bolt" })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = OnionClient::with_api_key("key".to_string()).unwrap();
+        let schema = item_schema();
+
+        let item: Item = client
+            .get(format!("{}/good", server.uri()))
+            .take_data_validated(&schema)
+            .await
+            .unwrap();
+        assert_eq!(
+            item,
+            Item {
+                id: 7,
+                name: "bolt".to_string()
+            }
+        );
+
+        let err = client
+            .get(format!("{}/bad", server.uri()))
+            .take_data_validated::<Item>(&schema)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("violates JSON Schema"), "{err}");
+        assert!(err.contains("/id"), "{err}");
+        assert!(err.contains("minimum"), "{err}");
+    }
+}
@@ -0,0 +1,58 @@
+#![cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct MockData {
+    foo: String,
+    bar: u32,
+}
+
+#[test]
+fn test_metrics_middleware_records_one_request() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    // The local recorder is thread-bound, so drive the request on this thread.
+    metrics::with_local_recorder(&recorder, || {
+        runtime.block_on(async {
+            let server = MockServer::start().await;
+            let mock_data = MockData {
+                foo: "measured".to_string(),
+                bar: 1,
+            };
+            Mock::given(method("GET"))
+                .and(path("/metrics"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(&mock_data))
+                .mount(&server)
+                .await;
+
+            let client = OnionClient::with_api_key("key".to_string()).unwrap();
+            let url = format!("{}{}", server.uri(), "/metrics");
+            let result: MockData = client.get(&url).take_data().await.unwrap();
+            assert_eq!(result, mock_data);
+        })
+    });
+
+    let snapshot = snapshotter.snapshot().into_vec();
+    let counters: Vec<_> = snapshot
+        .iter()
+        .filter(|(key, ..)| key.key().name() == REQUESTS_TOTAL_METRIC)
+        .collect();
+    assert_eq!(counters.len(), 1);
+
+    let (key, _, _, value) = counters[0];
+    assert!(key
+        .key()
+        .labels()
+        .any(|label| label.key() == "outcome" && label.value() == "success"));
+    assert_eq!(value, &DebugValue::Counter(1));
+}
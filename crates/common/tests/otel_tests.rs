@@ -0,0 +1,76 @@
+#![cfg(all(feature = "otel", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(test)]
+mod otel_tests {
+    use super::*;
+
+    async fn traced_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/traced"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_traceparent_is_injected_from_current_context() {
+        let server = traced_server().await;
+        let onion_client = OnionClientBuilder::default()
+            .retry(0u32)
+            .otel_propagation(true)
+            .build()
+            .unwrap();
+        let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::from_key_value([("vendor", "value")]).unwrap(),
+        );
+        let _guard = opentelemetry::Context::new()
+            .with_remote_span_context(span_context)
+            .attach();
+
+        client
+            .get(format!("{}/traced", server.uri()))
+            .send()
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(
+            requests[0].headers["traceparent"],
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+        assert_eq!(requests[0].headers["tracestate"], "vendor=value");
+    }
+
+    #[tokio::test]
+    async fn test_no_traceparent_without_active_context() {
+        let server = traced_server().await;
+        let onion_client = OnionClientBuilder::default()
+            .retry(0u32)
+            .otel_propagation(true)
+            .build()
+            .unwrap();
+        let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+
+        client
+            .get(format!("{}/traced", server.uri()))
+            .send()
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        assert!(!requests[0].headers.contains_key("traceparent"));
+    }
+}
@@ -0,0 +1,28 @@
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod percent_tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_encode_component() {
+        assert_eq!(percent_encode("a b&c=d"), "a%20b%26c%3Dd");
+        assert_eq!(percent_encode("héllo/wörld"), "h%C3%A9llo%2Fw%C3%B6rld");
+        assert_eq!(percent_encode("safe-._~"), "safe-._~");
+    }
+
+    #[test]
+    fn test_percent_decode_round_trip() {
+        let input = "tags=a&b c/ü";
+        assert_eq!(percent_decode(&percent_encode(input)).unwrap(), input);
+    }
+
+    #[test]
+    fn test_percent_decode_rejects_invalid_sequences() {
+        assert!(percent_decode("100%").is_err());
+        assert!(percent_decode("%zz").is_err());
+        assert!(percent_decode("%4").is_err());
+        // Well-formed escapes that aren't UTF-8.
+        assert!(percent_decode("%FF").is_err());
+    }
+}
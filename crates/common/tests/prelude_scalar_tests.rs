@@ -0,0 +1,23 @@
+#![cfg(feature = "scalar")]
+
+// Only the scalar prelude: this file must compile without `prelude::*`.
+use common_core::prelude::scalar::*;
+
+#[cfg(test)]
+mod prelude_scalar_tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_prelude_surface() {
+        let secret = Scalar::from(8675309u64);
+        let pubkey: RistrettoPoint = secret.compute_pubkey();
+        assert_eq!(pubkey, secret * RISTRETTO_BASEPOINT_POINT);
+
+        let encoded = pubkey.to_base58();
+        assert_eq!(RistrettoPoint::from_base58(encoded).unwrap(), pubkey);
+
+        let ring: PubRing = vec![pubkey];
+        let compressed: CompressedPubRing = ring.iter().map(compress_point).collect();
+        assert_eq!(try_decompress(&compressed[0]).unwrap(), pubkey);
+    }
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "qr")]
+
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod qr_tests {
+    use super::*;
+
+    #[test]
+    fn test_data_to_qr_png_creates_valid_base64_png() {
+        let data = b"Hello, world!";
+        let result = data_to_qr_png(data).unwrap();
+
+        assert!(result.starts_with("data:image/png;base64,"));
+
+        let base64_part = result.strip_prefix("data:image/png;base64,").unwrap();
+        let png_data = BS64ENGINE.decode(base64_part).unwrap();
+
+        // Check for PNG header
+        assert_eq!(&png_data[0..8], &[137, 80, 78, 71, 13, 10, 26, 10]);
+    }
+
+    #[test]
+    fn test_data_to_qr_png_chunked_yields_one_png_per_chunk() {
+        let data: Vec<u8> = (0..=249u8).collect();
+        let pngs = data_to_qr_png_chunked(&data, 100).unwrap();
+
+        assert_eq!(pngs.len(), 3);
+        assert!(pngs
+            .iter()
+            .all(|png| png.starts_with("data:image/png;base64,")));
+    }
+
+    #[test]
+    fn test_split_qr_payload_headers_cover_range() {
+        let data: Vec<u8> = (0..=249u8).collect();
+        let frames = split_qr_payload(&data, 100).unwrap();
+
+        let indices: Vec<u8> = frames.iter().map(|frame| frame[0]).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert!(frames.iter().all(|frame| frame[1] == 3));
+
+        let reassembled: Vec<u8> = frames
+            .iter()
+            .flat_map(|frame| frame[2..].to_vec())
+            .collect();
+        assert_eq!(reassembled, data);
+    }
+
+    #[test]
+    fn test_split_qr_payload_rejects_invalid_sizes() {
+        assert!(split_qr_payload(b"data", 0).is_err());
+        assert!(split_qr_payload(&[0u8; 256], 1).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_data_to_qr_png_async_matches_sync() {
+        let data = b"Hello, async world!";
+        let expected = data_to_qr_png(data).unwrap();
+        let actual = data_to_qr_png_async(data).await.unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    fn decode_png_data_url(data_url: &str) -> image::DynamicImage {
+        let base64_part = data_url.strip_prefix("data:image/png;base64,").unwrap();
+        let png_data = BS64ENGINE.decode(base64_part).unwrap();
+        image::load_from_memory_with_format(&png_data, image::ImageFormat::Png).unwrap()
+    }
+
+    #[test]
+    fn test_render_qr_png_default_matches_data_to_qr_png() {
+        let data = b"Hello, world!";
+        let rendered = render_qr_png(data, &QrOptions::default()).unwrap();
+        assert_eq!(rendered, data_to_qr_png(data).unwrap());
+
+        let image = decode_png_data_url(&rendered).to_rgb8();
+        // The quiet zone is light, so the top-left pixel is white.
+        assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_render_qr_png_custom_colors() {
+        let opts = QrOptions {
+            ecc: EcLevel::H,
+            scale: 4,
+            quiet_zone: false,
+            dark_color: [230, 230, 230],
+            light_color: [18, 18, 40],
+        };
+        let image = decode_png_data_url(&render_qr_png(b"dark mode", &opts).unwrap()).to_rgb8();
+
+        // Without a quiet zone the top-left corner is a dark finder module.
+        assert_eq!(image.get_pixel(0, 0).0, opts.dark_color);
+        assert!(image.pixels().any(|pixel| pixel.0 == opts.light_color));
+        assert_eq!(image.width() % opts.scale, 0);
+
+        let zero_scale = QrOptions {
+            scale: 0,
+            ..QrOptions::default()
+        };
+        assert!(render_qr_png(b"data", &zero_scale).is_err());
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_data_to_qr_png_batch_preserves_order() {
+        let items: Vec<Vec<u8>> = (0..12u8).map(|i| vec![b'a' + i; 10 + i as usize]).collect();
+
+        let pngs = data_to_qr_png_batch(&items).await.unwrap();
+
+        assert_eq!(pngs.len(), items.len());
+        for (item, png) in items.iter().zip(&pngs) {
+            assert_eq!(png, &data_to_qr_png(item).unwrap());
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tokio::test]
+    async fn test_data_to_qr_png_batch_reports_failing_index() {
+        // Far beyond the capacity of the largest QR version.
+        let items = vec![b"ok".to_vec(), vec![0u8; 8_000], b"ok".to_vec()];
+
+        let err = data_to_qr_png_batch(&items).await.unwrap_err();
+        assert!(err.to_string().contains("item 1"), "{err}");
+    }
+}
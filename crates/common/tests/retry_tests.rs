@@ -0,0 +1,62 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use common_core::prelude::*;
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod retry_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_retry_async_succeeds_after_failures() {
+        let mut calls = 0;
+        let result = retry_async(3, Duration::from_millis(1), None, || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    bail!("attempt {attempt} failed");
+                }
+                Ok(attempt)
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_async_returns_last_error() {
+        let err = retry_async(2, Duration::from_millis(1), None, || async {
+            AResult::<()>::Err(aerr!("still down"))
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "still down");
+    }
+
+    #[tokio::test]
+    async fn test_retry_async_cancel_interrupts_backoff() {
+        let cancel = CancellationToken::new();
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let started = Instant::now();
+        let err = retry_async(5, Duration::from_secs(30), Some(&cancel), || async {
+            AResult::<()>::Err(aerr!("flaky"))
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<CommonError>(),
+            Some(CommonError::Cancelled)
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
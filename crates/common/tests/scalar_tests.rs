@@ -1,4 +1,7 @@
+#![cfg(feature = "scalar")]
+
 use common_core::prelude::*;
+use std::str::FromStr;
 
 #[cfg(test)]
 mod scalar_tests {
@@ -8,7 +11,7 @@ mod scalar_tests {
     fn test_compute_pubkey() {
         let scalar = Scalar::from(12345u64);
         let pubkey = scalar.compute_pubkey();
-        assert_eq!(pubkey, &scalar * &RISTRETTO_BASEPOINT_POINT);
+        assert_eq!(pubkey, scalar * RISTRETTO_BASEPOINT_POINT);
     }
 
     #[test]
@@ -28,7 +31,7 @@ mod scalar_tests {
         let point = scalar.compute_pubkey();
 
         let base58_str = point.to_base58();
-        let recovered_point = RistrettoPoint::from_base58(base58_str).unwrap();
+        let recovered_point = RistrettoPoint::from_base58_str(&base58_str).unwrap();
 
         assert_eq!(point, recovered_point);
     }
@@ -51,4 +54,654 @@ mod scalar_tests {
         let result = RistrettoPoint::from_bytes(&bytes);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_compress_point_try_decompress_roundtrip() {
+        let point = Scalar::from(424242u64).compute_pubkey();
+
+        let compressed = compress_point(&point);
+        let ring: CompressedPubRing = vec![compressed];
+
+        assert_eq!(try_decompress(&ring[0]).unwrap(), point);
+    }
+
+    #[test]
+    fn test_non_canonical_bytes_fail_only_at_try_decompress() {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 0x10;
+
+        // Storing the raw array never validates it.
+        let ring: CompressedPubRing = vec![bytes];
+        assert_eq!(ring.len(), 1);
+
+        assert!(try_decompress(&ring[0]).is_err());
+    }
+
+    #[test]
+    fn test_mul_base_matches_point_multiplication() {
+        for k in [0u64, 1, 2, 12345, u64::MAX] {
+            let scalar = Scalar::from(k);
+            assert_eq!(mul_base(&scalar), scalar * RISTRETTO_BASEPOINT_POINT);
+        }
+    }
+
+    #[test]
+    fn test_pubkey_matches_secret() {
+        let secret = Scalar::from(777u64);
+        let public = secret.compute_pubkey();
+        assert!(pubkey_matches(&secret, &public));
+    }
+
+    #[test]
+    fn test_pubkey_matches_rejects_swapped_key() {
+        let secret = Scalar::from(777u64);
+        let other_public = Scalar::from(778u64).compute_pubkey();
+        assert!(!pubkey_matches(&secret, &other_public));
+    }
+
+    #[test]
+    fn test_point58_parse_success() {
+        let point = Scalar::from(4242u64).compute_pubkey();
+        let parsed: Point58 = point.to_base58().parse().unwrap();
+        assert_eq!(parsed.point(), point);
+    }
+
+    #[test]
+    fn test_point58_rejects_invalid_base58() {
+        // '0' and 'O' are not in the bitcoin base58 alphabet.
+        assert!("0OIl".parse::<Point58>().is_err());
+    }
+
+    #[test]
+    fn test_point58_display_roundtrip() {
+        let point = Point58::from(Scalar::from(99u64).compute_pubkey());
+        let recovered = Point58::from_str(&point.to_string()).unwrap();
+
+        assert_eq!(recovered, point);
+        assert_eq!(display_point(&point.point()), point.to_string());
+    }
+
+    #[test]
+    fn test_point_id_is_stable() {
+        let point = Scalar::from(5150u64).compute_pubkey();
+        assert_eq!(point_id(&point), point_id(&point));
+        assert_eq!(point_id_str(&point), point_id_str(&point));
+    }
+
+    #[test]
+    fn test_point_id_differs_for_distinct_points() {
+        let a = Scalar::from(1u64).compute_pubkey();
+        let b = Scalar::from(2u64).compute_pubkey();
+        assert_ne!(point_id(&a), point_id(&b));
+        assert_ne!(point_id_str(&a), point_id_str(&b));
+    }
+
+    #[test]
+    fn test_verify_pubring_all_matching() {
+        let secrets: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+        let ring: PubRing = secrets.iter().map(|s| s.compute_pubkey()).collect();
+        assert!(verify_pubring(&secrets, &ring).is_ok());
+    }
+
+    #[test]
+    fn test_verify_pubring_reports_mismatching_index() {
+        let secrets: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+        let mut ring: PubRing = secrets.iter().map(|s| s.compute_pubkey()).collect();
+        ring[2] = Scalar::from(99u64).compute_pubkey();
+
+        let error = verify_pubring(&secrets, &ring).unwrap_err().to_string();
+        assert!(error.contains("index 2"));
+    }
+
+    #[test]
+    fn test_verify_pubring_length_mismatch() {
+        let secrets: Vec<Scalar> = (1..=3u64).map(Scalar::from).collect();
+        let ring: PubRing = secrets[..2].iter().map(|s| s.compute_pubkey()).collect();
+        assert!(verify_pubring(&secrets, &ring).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_keys_is_deterministic() {
+        let ring: PubRing = (1..=3u64)
+            .map(|k| Scalar::from(k).compute_pubkey())
+            .collect();
+
+        let (agg_a, coefficients_a) = aggregate_keys(&ring);
+        let (agg_b, coefficients_b) = aggregate_keys(&ring);
+
+        assert_eq!(agg_a, agg_b);
+        assert_eq!(coefficients_a, coefficients_b);
+        assert_eq!(coefficients_a.len(), ring.len());
+
+        let expected = ring
+            .iter()
+            .zip(&coefficients_a)
+            .map(|(key, coefficient)| coefficient * key)
+            .sum::<RistrettoPoint>();
+        assert_eq!(agg_a, expected);
+    }
+
+    #[test]
+    fn test_aggregate_keys_depends_on_order() {
+        let ring: PubRing = (1..=3u64)
+            .map(|k| Scalar::from(k).compute_pubkey())
+            .collect();
+        let mut reordered = ring.clone();
+        reordered.reverse();
+
+        let (agg, coefficients) = aggregate_keys(&ring);
+        let (agg_reordered, coefficients_reordered) = aggregate_keys(&reordered);
+
+        // The key list is hashed in order, so every coefficient changes...
+        assert_ne!(coefficients[0], coefficients_reordered[2]);
+        // ...and with it the aggregate key; signers must agree on an order.
+        assert_ne!(agg, agg_reordered);
+    }
+
+    #[test]
+    fn test_scalar_from_u128_matches_manual_construction() {
+        let n: u128 = (7u128 << 64) + 11;
+        let manual = Scalar::from(7u64) * Scalar::from(1u128 << 64) + Scalar::from(11u64);
+        assert_eq!(scalar_from_u128(n), manual);
+    }
+
+    #[test]
+    fn test_scalar_from_le_bytes() {
+        let scalar = Scalar::from(123456789u64);
+        assert_eq!(scalar_from_le_bytes(scalar.to_bytes()).unwrap(), scalar);
+
+        // 2^256 - 1 is far above the group order.
+        assert!(scalar_from_le_bytes([0xff; 32]).is_err());
+    }
+
+    #[test]
+    fn test_from_base58_str_matches_owned_variant() {
+        let encoded = Scalar::from(31337u64).compute_pubkey().to_base58();
+        assert_eq!(
+            RistrettoPoint::from_base58_str(&encoded).unwrap(),
+            RistrettoPoint::from_base58(encoded.clone()).unwrap()
+        );
+
+        let invalid = "0OIl";
+        assert!(RistrettoPoint::from_base58_str(invalid).is_err());
+        assert!(RistrettoPoint::from_base58(invalid.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_pubring_merkle_root_is_order_dependent() {
+        let ring: PubRing = (1u64..=3)
+            .map(|k| Scalar::from(k).compute_pubkey())
+            .collect();
+        let mut reordered = ring.clone();
+        reordered.swap(0, 1);
+
+        assert_eq!(
+            pubring_merkle_root(&ring),
+            pubring_merkle_root(&ring.clone())
+        );
+        assert_ne!(pubring_merkle_root(&ring), pubring_merkle_root(&reordered));
+    }
+
+    #[test]
+    fn test_pubring_merkle_proof_verifies() {
+        let ring: PubRing = (1u64..=5)
+            .map(|k| Scalar::from(k).compute_pubkey())
+            .collect();
+        let root = pubring_merkle_root(&ring);
+
+        for (index, key) in ring.iter().enumerate() {
+            let proof = pubring_merkle_proof(&ring, index).unwrap();
+            assert!(verify_pubring_merkle_proof(
+                &root,
+                key,
+                index,
+                ring.len(),
+                &proof
+            ));
+        }
+
+        // The proof is bound to its position and key.
+        let proof = pubring_merkle_proof(&ring, 0).unwrap();
+        assert!(!verify_pubring_merkle_proof(
+            &root,
+            &ring[0],
+            1,
+            ring.len(),
+            &proof
+        ));
+        assert!(!verify_pubring_merkle_proof(
+            &root,
+            &ring[1],
+            0,
+            ring.len(),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_pubring_merkle_proof_out_of_range() {
+        let ring: PubRing = vec![Scalar::from(1u64).compute_pubkey()];
+        assert!(pubring_merkle_proof(&ring, 1).is_err());
+        assert!(pubring_merkle_proof(&PubRing::new(), 0).is_err());
+    }
+
+    #[test]
+    fn test_point_arithmetic() {
+        let a = Scalar::from(11u64).compute_pubkey();
+        let b = Scalar::from(5u64).compute_pubkey();
+        let identity = curve25519_dalek::traits::Identity::identity();
+
+        assert_eq!(point_add(&a, &point_neg(&a)), identity);
+        assert_eq!(point_sub(&a, &b), point_add(&a, &point_neg(&b)));
+        assert_eq!(point_sub(&a, &b), Scalar::from(6u64).compute_pubkey());
+    }
+
+    #[test]
+    fn test_valid_point_try_from() {
+        let pubkey = Scalar::from(2024u64).compute_pubkey();
+        let bytes = compress_point(&pubkey);
+
+        let from_array = ValidPoint::try_from(bytes).unwrap();
+        assert_eq!(*from_array, pubkey);
+        let from_slice = ValidPoint::try_from(bytes.as_slice()).unwrap();
+        assert_eq!(from_slice, from_array);
+
+        // Field elements >= p are not canonical encodings.
+        assert!(ValidPoint::try_from([0xff; 32]).is_err());
+        assert!(ValidPoint::try_from(&bytes[..31]).is_err());
+    }
+
+    #[test]
+    fn test_pubkey_from_base58() {
+        let pubkey = Scalar::from(777u64).compute_pubkey();
+        assert_eq!(pubkey_from_base58(&pubkey.to_base58()).unwrap(), pubkey);
+
+        let short = bs58::encode([7u8; 31]).into_string();
+        let err = pubkey_from_base58(&short).unwrap_err();
+        assert_eq!(err.to_string(), "Expected 32-byte pubkey, got 31");
+    }
+
+    #[test]
+    fn test_compute_pubkeys_matches_per_key() {
+        let scalars: Vec<Scalar> = (1..=8u64).map(Scalar::from).collect();
+        let ring = compute_pubkeys(&scalars);
+        assert_eq!(ring.len(), scalars.len());
+        assert!(scalars
+            .iter()
+            .zip(&ring)
+            .all(|(k, p)| k.compute_pubkey() == *p));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_compute_pubkeys_par_matches_serial() {
+        let scalars: Vec<Scalar> = (1..=257u64).map(Scalar::from).collect();
+        assert_eq!(compute_pubkeys_par(&scalars), compute_pubkeys(&scalars));
+    }
+
+    #[test]
+    fn test_elgamal_round_trip() {
+        let secret = Scalar::from(4242u64);
+        let pubkey = secret.compute_pubkey();
+        let message = Scalar::from(17u64).compute_pubkey();
+
+        let ciphertext = elgamal_encrypt(&pubkey, &message).unwrap();
+        assert_eq!(elgamal_decrypt(&secret, ciphertext), message);
+        // A fresh ephemeral scalar makes repeated encryptions differ.
+        assert_ne!(elgamal_encrypt(&pubkey, &message).unwrap(), ciphertext);
+    }
+
+    #[test]
+    fn test_elgamal_is_additively_homomorphic() {
+        let secret = Scalar::from(99u64);
+        let pubkey = secret.compute_pubkey();
+        let m1 = Scalar::from(3u64).compute_pubkey();
+        let m2 = Scalar::from(4u64).compute_pubkey();
+
+        let sum = elgamal_add(
+            elgamal_encrypt(&pubkey, &m1).unwrap(),
+            elgamal_encrypt(&pubkey, &m2).unwrap(),
+        );
+        assert_eq!(elgamal_decrypt(&secret, sum), point_add(&m1, &m2));
+    }
+
+    #[derive(Serialize)]
+    struct Transfer {
+        from: String,
+        to: String,
+        amount: u64,
+    }
+
+    #[test]
+    fn test_sign_and_verify_payload() {
+        let secret = Scalar::from(123_456u64);
+        let public = secret.compute_pubkey();
+        let mut transfer = Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 10,
+        };
+
+        let signature = sign_payload(&secret, &transfer).unwrap();
+        assert!(verify_payload(&public, &transfer, &signature).unwrap());
+
+        let decoded = Signature::from_bytes(&signature.to_bytes()).unwrap();
+        assert_eq!(decoded, signature);
+
+        transfer.amount = 1_000;
+        assert!(!verify_payload(&public, &transfer, &signature).unwrap());
+
+        let other = Scalar::from(7u64).compute_pubkey();
+        transfer.amount = 10;
+        assert!(!verify_payload(&other, &transfer, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_is_canonical_encoding() {
+        let pubkey = compress_point(&Scalar::from(31u64).compute_pubkey());
+        assert!(is_canonical_encoding(&pubkey));
+
+        // The invalid-point vector above is a canonical field element: it passes
+        // the cheap check and is only rejected by full decompression.
+        let mut not_on_curve = [0u8; 32];
+        not_on_curve[31] = 0x10;
+        assert!(is_canonical_encoding(&not_on_curve));
+        assert!(try_decompress(&not_on_curve).is_err());
+
+        let mut negative = pubkey;
+        negative[0] |= 1;
+        assert!(!is_canonical_encoding(&negative));
+        assert!(!is_canonical_encoding(&[0xff; 32]));
+
+        // p - 1 is the largest reduced value; p + 1 is even but not reduced.
+        let mut p = [0xff; 32];
+        p[0] = 0xec;
+        p[31] = 0x7f;
+        assert!(is_canonical_encoding(&p));
+        p[0] = 0xee;
+        assert!(!is_canonical_encoding(&p));
+    }
+
+    #[test]
+    fn test_sort_pubring_is_order_independent() {
+        let ring: PubRing = (1u64..=6)
+            .map(|k| Scalar::from(k).compute_pubkey())
+            .collect();
+        let mut shuffled = ring.clone();
+        shuffled.reverse();
+        shuffled.swap(0, 3);
+
+        let sorted = sort_pubring(&ring);
+        assert_eq!(sorted, sort_pubring(&shuffled));
+        assert!(sorted
+            .windows(2)
+            .all(|pair| compress_point(&pair[0]) <= compress_point(&pair[1])));
+        assert_eq!(
+            pubring_merkle_root(&sorted),
+            pubring_merkle_root(&sort_pubring(&shuffled))
+        );
+    }
+
+    #[test]
+    fn test_scalar_canonical_vs_reducing_construction() {
+        let canonical = Scalar::from(5u64).to_bytes();
+        assert_eq!(
+            scalar_from_canonical_bytes(canonical).unwrap(),
+            Scalar::from(5u64)
+        );
+        assert_eq!(scalar_from_bytes_mod_order(canonical), Scalar::from(5u64));
+
+        // l + 5, where l is the group order.
+        let mut non_canonical = [0u8; 32];
+        let l = (-Scalar::ONE).to_bytes();
+        let mut carry = 6u16;
+        for (out, byte) in non_canonical.iter_mut().zip(l) {
+            let sum = u16::from(byte) + carry;
+            *out = sum as u8;
+            carry = sum >> 8;
+        }
+        assert!(scalar_from_canonical_bytes(non_canonical).is_err());
+        assert_eq!(
+            scalar_from_bytes_mod_order(non_canonical),
+            Scalar::from(5u64)
+        );
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_deterministic_pubring() {
+        let (secrets, ring) = deterministic_pubring(42, 5);
+        assert_eq!(
+            deterministic_pubring(42, 5),
+            (secrets.clone(), ring.clone())
+        );
+        assert_ne!(deterministic_pubring(43, 5).1, ring);
+
+        assert_eq!(ring.len(), 5);
+        verify_pubring(&secrets, &ring).unwrap();
+    }
+
+    #[test]
+    fn test_commit_sub_opens_to_difference() {
+        let (v1, b1) = (Scalar::from(100u64), Scalar::from(7u64));
+        let (v2, b2) = (Scalar::from(58u64), Scalar::from(3u64));
+
+        let difference = commit_sub(&pedersen_commit(&v1, &b1), &pedersen_commit(&v2, &b2));
+        assert_eq!(difference, pedersen_commit(&(v1 - v2), &(b1 - b2)));
+        assert_ne!(difference, pedersen_commit(&(v1 - v2), &b1));
+    }
+
+    #[test]
+    fn test_schnorr_verify_detailed_reasons() {
+        let secret = Scalar::from(1_001u64);
+        let public = secret.compute_pubkey();
+        let signature = schnorr_sign(&secret, b"message").to_bytes();
+
+        assert_eq!(
+            schnorr_verify_detailed(&public, b"message", &signature),
+            Ok(())
+        );
+        assert_eq!(
+            schnorr_verify_detailed(&public, b"other message", &signature),
+            Err(VerifyError::ChallengeMismatch)
+        );
+        let wrong_key = Scalar::from(2u64).compute_pubkey();
+        assert_eq!(
+            schnorr_verify_detailed(&wrong_key, b"message", &signature),
+            Err(VerifyError::ChallengeMismatch)
+        );
+
+        let mut non_canonical_s = signature;
+        non_canonical_s[32..].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            schnorr_verify_detailed(&public, b"message", &non_canonical_s),
+            Err(VerifyError::MalformedSignature(_))
+        ));
+
+        let mut invalid_r = signature;
+        invalid_r[..32].copy_from_slice(&[0xff; 32]);
+        assert!(matches!(
+            schnorr_verify_detailed(&public, b"message", &invalid_r),
+            Err(VerifyError::MalformedSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_batch_invert_matches_individual_inversion() {
+        let originals: Vec<Scalar> = (1..=5u64).map(|n| Scalar::from(n * 7)).collect();
+        let mut scalars = originals.clone();
+
+        let product = batch_invert(&mut scalars).unwrap();
+
+        assert_eq!(product, originals.iter().product::<Scalar>());
+        for (inverted, original) in scalars.iter().zip(&originals) {
+            assert_eq!(*inverted, original.invert());
+        }
+    }
+
+    #[test]
+    fn test_batch_invert_rejects_zero() {
+        let mut scalars = vec![Scalar::from(3u64), Scalar::ZERO, Scalar::from(5u64)];
+        let err = batch_invert(&mut scalars).unwrap_err();
+
+        assert!(err.to_string().contains("index 1"));
+        assert_eq!(scalars[0], Scalar::from(3u64));
+    }
+
+    #[test]
+    fn test_signature_byte_convertible_round_trip() {
+        let signature = schnorr_sign(&Scalar::from(77u64), b"wire");
+        let bytes: [u8; 64] = signature.to_bytes();
+
+        assert_eq!(Signature::from_bytes(&bytes).unwrap(), signature);
+        assert_eq!(
+            Signature::from_base58_str(&signature.to_base58()).unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn test_signature_from_bytes_rejects_bad_input() {
+        let bytes = schnorr_sign(&Scalar::from(77u64), b"wire").to_bytes();
+
+        let err = Signature::from_bytes(&bytes[..63]).unwrap_err();
+        assert!(err.to_string().contains("got 63"));
+
+        let mut non_canonical_s = bytes;
+        non_canonical_s[32..].copy_from_slice(&[0xff; 32]);
+        assert!(Signature::from_bytes(&non_canonical_s).is_err());
+
+        let mut invalid_r = bytes;
+        invalid_r[..32].copy_from_slice(&[0xff; 32]);
+        assert!(Signature::from_bytes(&invalid_r).is_err());
+    }
+
+    #[test]
+    fn test_compressed_ristretto_base58_round_trip() {
+        let compressed = Scalar::from(99u64).compute_pubkey().compress();
+        let encoded = LocalByteConvertible::to_base58(&compressed);
+
+        assert_eq!(encoded, Scalar::from(99u64).compute_pubkey().to_base58());
+        assert_eq!(
+            CompressedRistretto::from_base58_str(&encoded).unwrap(),
+            compressed
+        );
+        assert_eq!(
+            compressed_from_base58_checked(&encoded).unwrap(),
+            compressed
+        );
+    }
+
+    #[test]
+    fn test_compressed_ristretto_checked_decode_rejects_invalid_point() {
+        let encoded = bs58::encode([0xff; 32]).into_string();
+
+        let unchecked = CompressedRistretto::from_base58_str(&encoded).unwrap();
+        assert_eq!(unchecked.to_bytes(), [0xff; 32]);
+        assert!(compressed_from_base58_checked(&encoded).is_err());
+        assert!(
+            CompressedRistretto::from_base58_str(&bs58::encode([1u8; 31]).into_string()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_find_vanity_keypair_one_char_prefix() {
+        let keypair = find_vanity_keypair("4", 10_000).unwrap();
+
+        assert!(keypair.public().to_base58().starts_with('4'));
+        assert_eq!(keypair.secret().compute_pubkey(), keypair.public());
+        assert!(!format!("{keypair:?}").contains(&format!("{:?}", keypair.secret())));
+    }
+
+    #[test]
+    fn test_find_vanity_keypair_errors() {
+        let err = find_vanity_keypair("a0", 10).unwrap_err();
+        assert!(err.to_string().contains("'0'"), "{err}");
+
+        let err = find_vanity_keypair("zzzzzzzz", 10).unwrap_err();
+        assert!(err.to_string().contains("10 attempts"), "{err}");
+    }
+
+    #[test]
+    fn test_reject_identity() {
+        use curve25519_dalek::traits::Identity;
+
+        assert!(reject_identity(&RistrettoPoint::identity()).is_err());
+        // The identity also decodes from the all-zero encoding.
+        assert!(reject_identity(&try_decompress(&[0u8; 32]).unwrap()).is_err());
+        assert!(reject_identity(&random_scalar().unwrap().compute_pubkey()).is_ok());
+    }
+
+    #[test]
+    fn test_key_image_matches_blsag() {
+        use nazgul::traits::KeyImageGen;
+
+        let secret = Scalar::from(4_242u64);
+        assert_eq!(
+            key_image(&secret),
+            BLSAG::generate_key_image::<Keccak512>(secret)
+        );
+    }
+
+    #[test]
+    fn test_any_key_image_seen() {
+        let secrets: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+        let images = key_images(&secrets);
+
+        let mut seen: std::collections::HashSet<PointKey> = [key_image(&Scalar::from(99u64))]
+            .iter()
+            .map(PointKey::from)
+            .collect();
+        assert_eq!(any_key_image_seen(&images, &seen), None);
+
+        seen.insert(PointKey::from(images[2]));
+        seen.insert(PointKey::from(&images[3]));
+        assert_eq!(any_key_image_seen(&images, &seen), Some(2));
+    }
+
+    #[test]
+    fn test_shamir_any_threshold_subset_recombines() {
+        let secret = random_scalar().unwrap();
+        let shares = split_secret(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset = [shares[a], shares[b], shares[c]];
+                    assert_eq!(recombine(&subset).unwrap(), secret);
+                }
+            }
+        }
+        assert_eq!(recombine(&shares).unwrap(), secret);
+        assert_ne!(recombine(&shares[..2]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_shamir_rejects_bad_parameters_and_shares() {
+        let secret = Scalar::from(5u64);
+        assert!(split_secret(&secret, 0, 3).is_err());
+        assert!(split_secret(&secret, 4, 3).is_err());
+        assert!(split_secret(&secret, 2, 256).is_err());
+        assert_eq!(split_secret(&secret, 1, 255).unwrap()[254].1, secret);
+
+        let shares = split_secret(&secret, 2, 3).unwrap();
+        assert!(recombine(&[]).is_err());
+        assert!(recombine(&[shares[0], shares[0]]).is_err());
+        assert!(recombine(&[(0, secret), shares[1]]).is_err());
+    }
+
+    #[test]
+    fn test_pubring_difference_and_intersection() {
+        let keys: PubRing = (1..=6u64)
+            .map(|n| Scalar::from(n).compute_pubkey())
+            .collect();
+        let a: PubRing = vec![keys[4], keys[0], keys[2], keys[1]];
+        let b: PubRing = vec![keys[1], keys[5], keys[4]];
+
+        assert_eq!(pubring_difference(&a, &b), vec![keys[0], keys[2]]);
+        assert_eq!(pubring_intersection(&a, &b), vec![keys[4], keys[1]]);
+        assert_eq!(pubring_difference(&a, &PubRing::new()), a);
+        assert!(pubring_intersection(&a, &PubRing::new()).is_empty());
+    }
 }
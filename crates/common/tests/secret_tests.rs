@@ -0,0 +1,35 @@
+#![cfg(feature = "zeroize")]
+
+use common_core::prelude::*;
+
+#[cfg(test)]
+mod secret_tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_zeroizing_base58_round_trip() {
+        let scalar = Scalar::from(987654321u64);
+        let secret = SecretScalar::new(scalar);
+
+        let encoded = secret_to_zeroizing_base58(&secret);
+        assert_eq!(*encoded, bs58::encode(scalar.to_bytes()).into_string());
+
+        let decoded = secret_from_zeroizing_base58(&encoded).unwrap();
+        assert_eq!(*decoded, scalar.to_bytes());
+    }
+
+    #[test]
+    fn test_secret_from_zeroizing_base58_rejects_wrong_length() {
+        let short = bs58::encode([1u8; 16]).into_string();
+        assert!(secret_from_zeroizing_base58(&short).is_err());
+
+        let long = bs58::encode([1u8; 33]).into_string();
+        assert!(secret_from_zeroizing_base58(&long).is_err());
+    }
+
+    #[test]
+    fn test_secret_scalar_debug_is_redacted() {
+        let secret = SecretScalar::from(Scalar::from(42u64));
+        assert_eq!(format!("{secret:?}"), "SecretScalar(..)");
+    }
+}
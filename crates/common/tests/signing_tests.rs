@@ -0,0 +1,84 @@
+#![cfg(all(feature = "hash", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[cfg(test)]
+mod signing_tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_request_parts_is_stable() {
+        let signature =
+            sign_request_parts(b"secret", "POST", "/v1/orders?dry=1", 1_700_000_000, b"{}");
+
+        assert_eq!(
+            signature,
+            sign_request_parts(b"secret", "POST", "/v1/orders?dry=1", 1_700_000_000, b"{}")
+        );
+        // Independently computed with Python's `hmac` module.
+        assert_eq!(
+            signature,
+            "e6f4636d8a3b3c1b76592319a1267871cdd4b504f73c0c49ce40085aa54313a9"
+        );
+        assert_ne!(
+            signature,
+            sign_request_parts(b"secret", "POST", "/v1/orders?dry=2", 1_700_000_000, b"{}")
+        );
+        assert_ne!(
+            signature,
+            sign_request_parts(b"secret", "POST", "/v1/orders?dry=1", 1_700_000_001, b"{}")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signing_middleware_sets_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/orders"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let signing = SigningMiddleware::new(b"secret".to_vec(), "x-signature").unwrap();
+        let onion_client = OnionClientBuilder::default()
+            .retry(0u32)
+            .request_signing(Some(signing))
+            .build()
+            .unwrap();
+        let client: ClientWithMiddleware = AResult::from(onion_client).unwrap();
+
+        client
+            .post(format!("{}/v1/orders?dry=1", server.uri()))
+            .body(r#"{"sku":"A1"}"#)
+            .send()
+            .await
+            .unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let headers = &requests[0].headers;
+        let timestamp: u64 = headers[SIGNATURE_TIMESTAMP_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            headers["x-signature"],
+            sign_request_parts(
+                b"secret",
+                "POST",
+                "/v1/orders?dry=1",
+                timestamp,
+                br#"{"sku":"A1"}"#
+            )
+            .as_str()
+        );
+    }
+
+    #[test]
+    fn test_signing_middleware_rejects_bad_header_name() {
+        assert!(SigningMiddleware::new(b"secret".to_vec(), "bad header").is_err());
+    }
+}
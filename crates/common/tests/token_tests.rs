@@ -0,0 +1,47 @@
+#![cfg(feature = "hash")]
+
+use common_core::prelude::*;
+use std::time::Duration;
+
+#[cfg(test)]
+mod token_tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"server-side secret";
+
+    #[test]
+    fn test_signed_token_round_trip() {
+        let token = make_signed_token(SECRET, b"user:42", Duration::from_secs(60));
+
+        assert_eq!(verify_signed_token(SECRET, &token).unwrap(), b"user:42");
+    }
+
+    #[test]
+    fn test_signed_token_long_secret_round_trip() {
+        let secret = [7u8; 100];
+        let token = make_signed_token(&secret, b"payload", Duration::from_secs(60));
+
+        assert_eq!(verify_signed_token(&secret, &token).unwrap(), b"payload");
+        assert!(verify_signed_token(SECRET, &token).is_err());
+    }
+
+    #[test]
+    fn test_signed_token_rejects_tampering() {
+        let token = make_signed_token(SECRET, b"user:42", Duration::from_secs(60));
+        let mut bytes = bs58::decode(&token).into_vec().unwrap();
+        bytes[5] ^= 0x01;
+        let tampered = bs58::encode(bytes).into_string();
+
+        let err = verify_signed_token(SECRET, &tampered).unwrap_err();
+        assert!(err.to_string().contains("invalid signature"));
+        assert!(verify_signed_token(b"another secret", &token).is_err());
+    }
+
+    #[test]
+    fn test_signed_token_rejects_expired() {
+        let token = make_signed_token(SECRET, b"user:42", Duration::ZERO);
+
+        let err = verify_signed_token(SECRET, &token).unwrap_err();
+        assert!(err.to_string().contains("expired"));
+    }
+}
@@ -0,0 +1,43 @@
+#![cfg(all(feature = "tor", not(target_arch = "wasm32")))]
+
+use common_core::http_client::*;
+use common_core::prelude::*;
+use std::net::SocketAddr;
+
+#[cfg(test)]
+mod tor_tests {
+    use super::*;
+
+    #[test]
+    fn test_tor_socks_defaults_to_local_daemon() {
+        let config = OnionClientBuilder::default().build().unwrap();
+        assert_eq!(
+            config.tor_socks(),
+            Some(SocketAddr::from(([127, 0, 0, 1], 9050)))
+        );
+
+        let custom: SocketAddr = "10.0.0.2:9150".parse().unwrap();
+        let config = OnionClientBuilder::default()
+            .tor_socks(custom)
+            .build()
+            .unwrap();
+        assert_eq!(config.tor_socks(), Some(custom));
+
+        let client: AResult<ClientWithMiddleware> = config.into();
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_onion_proxy_url_only_routes_onion_hosts() {
+        let tor_socks: SocketAddr = "127.0.0.1:9050".parse().unwrap();
+
+        let onion = Url::parse("http://exampleonionaddress.onion/path").unwrap();
+        assert_eq!(
+            onion_proxy_url(tor_socks, &onion).unwrap().as_str(),
+            "socks5h://127.0.0.1:9050"
+        );
+
+        let clearnet = Url::parse("https://example.com/").unwrap();
+        assert_eq!(onion_proxy_url(tor_socks, &clearnet), None);
+    }
+}
@@ -0,0 +1,15 @@
+#![cfg(target_arch = "wasm32")]
+
+use common_core::http_client::*;
+use wasm_bindgen_test::*;
+
+#[wasm_bindgen_test]
+fn test_fetch_options_chain() {
+    let request = Client::new()
+        .get("https://example.com/api")
+        .with_credentials_include()
+        .with_cors_mode(CorsMode::NoCors)
+        .build();
+
+    assert!(request.is_ok());
+}
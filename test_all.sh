@@ -16,6 +16,13 @@ cargo test --all-features
 echo "--- Native Tests Passed ---"
 echo
 
+# Optional features such as 'qr' and 'scalar' must stay optional: the crate and
+# its tests have to build with every default feature switched off.
+echo "--- Running Native Tests (no default features) ---"
+cargo test --no-default-features
+echo "--- Native No-Default-Features Tests Passed ---"
+echo
+
 # --- Wasm Target Compilation Checks ---
 # For the wasm32-unknown-unknown target, we can't run tests directly in a standard
 # environment without a specific Wasm test runner (like wasm-pack).